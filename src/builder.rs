@@ -0,0 +1,353 @@
+use indexmap::IndexMap;
+
+use crate::types::*;
+use crate::validation::{ValidationError, ValidationErrorKind};
+
+/// Fluent construction of a `Configuration`.
+/// Names are checked for duplicates and the result is validated in `build`.
+#[derive(Clone, Debug)]
+pub struct ConfigurationBuilder {
+    title: Option<String>,
+    description: Option<String>,
+    samplerate: usize,
+    chunksize: usize,
+    capture: Option<CaptureDevice>,
+    playback: Option<PlaybackDevice>,
+    queuelimit: Option<usize>,
+    silence_threshold: Option<f64>,
+    silence_timeout: Option<f64>,
+    enable_rate_adjust: Option<bool>,
+    target_level: Option<usize>,
+    adjust_period: Option<f32>,
+    resampler: Option<Resampler>,
+    capture_samplerate: Option<usize>,
+    stop_on_rate_change: Option<bool>,
+    rate_measure_interval: Option<f32>,
+    volume_ramp_time: Option<f32>,
+    volume_limit: Option<f32>,
+    multithreaded: Option<bool>,
+    worker_threads: Option<usize>,
+    mixers: IndexMap<String, Mixer>,
+    filters: IndexMap<String, Filter>,
+    processors: IndexMap<String, Processor>,
+    pipeline: Vec<PipelineStep>,
+    errors: Vec<ValidationError>,
+}
+
+impl ConfigurationBuilder {
+    pub fn new(samplerate: usize, chunksize: usize) -> Self {
+        ConfigurationBuilder {
+            title: None,
+            description: None,
+            samplerate,
+            chunksize,
+            capture: None,
+            playback: None,
+            queuelimit: None,
+            silence_threshold: None,
+            silence_timeout: None,
+            enable_rate_adjust: None,
+            target_level: None,
+            adjust_period: None,
+            resampler: None,
+            capture_samplerate: None,
+            stop_on_rate_change: None,
+            rate_measure_interval: None,
+            volume_ramp_time: None,
+            volume_limit: None,
+            multithreaded: None,
+            worker_threads: None,
+            mixers: IndexMap::new(),
+            filters: IndexMap::new(),
+            processors: IndexMap::new(),
+            pipeline: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn capture(mut self, device: CaptureDevice) -> Self {
+        self.capture = Some(device);
+        self
+    }
+
+    pub fn playback(mut self, device: PlaybackDevice) -> Self {
+        self.playback = Some(device);
+        self
+    }
+
+    pub fn queuelimit(mut self, queuelimit: usize) -> Self {
+        self.queuelimit = Some(queuelimit);
+        self
+    }
+
+    pub fn silence(mut self, threshold: f64, timeout: f64) -> Self {
+        self.silence_threshold = Some(threshold);
+        self.silence_timeout = Some(timeout);
+        self
+    }
+
+    pub fn rate_adjust(mut self, target_level: usize) -> Self {
+        self.enable_rate_adjust = Some(true);
+        self.target_level = Some(target_level);
+        self
+    }
+
+    pub fn adjust_period(mut self, period: f32) -> Self {
+        self.adjust_period = Some(period);
+        self
+    }
+
+    pub fn resampler(mut self, resampler: Resampler, capture_samplerate: usize) -> Self {
+        self.resampler = Some(resampler);
+        self.capture_samplerate = Some(capture_samplerate);
+        self
+    }
+
+    pub fn stop_on_rate_change(mut self) -> Self {
+        self.stop_on_rate_change = Some(true);
+        self
+    }
+
+    pub fn rate_measure_interval(mut self, interval: f32) -> Self {
+        self.rate_measure_interval = Some(interval);
+        self
+    }
+
+    pub fn volume(mut self, ramp_time: f32, limit: f32) -> Self {
+        self.volume_ramp_time = Some(ramp_time);
+        self.volume_limit = Some(limit);
+        self
+    }
+
+    /// Process the filters of the pipeline in parallel, with the given number of
+    /// threads or with one per CPU core when `worker_threads` is `None`.
+    pub fn multithreaded(mut self, worker_threads: Option<usize>) -> Self {
+        self.multithreaded = Some(true);
+        self.worker_threads = worker_threads;
+        self
+    }
+
+    fn insert<T>(
+        map: &mut IndexMap<String, T>,
+        errors: &mut Vec<ValidationError>,
+        section: &str,
+        name: &str,
+        value: T,
+    ) {
+        if map.insert(name.to_string(), value).is_some() {
+            errors.push(ValidationError::new(
+                format!("{}.{}", section, name),
+                ValidationErrorKind::DuplicateName(name.to_string()),
+            ));
+        }
+    }
+
+    pub fn filter(mut self, name: &str, filter: Filter) -> Self {
+        Self::insert(&mut self.filters, &mut self.errors, "filters", name, filter);
+        self
+    }
+
+    pub fn mixer(mut self, name: &str, mixer: Mixer) -> Self {
+        Self::insert(&mut self.mixers, &mut self.errors, "mixers", name, mixer);
+        self
+    }
+
+    pub fn processor(mut self, name: &str, processor: Processor) -> Self {
+        Self::insert(
+            &mut self.processors,
+            &mut self.errors,
+            "processors",
+            name,
+            processor,
+        );
+        self
+    }
+
+    /// Add a filter step for the given channels.
+    pub fn step_filter(mut self, channels: &[usize], names: &[&str]) -> Self {
+        self.pipeline.push(PipelineStep::Filter(PipelineStepFilter {
+            channels: Some(channels.to_vec()),
+            names: names.iter().map(|name| name.to_string()).collect(),
+            description: None,
+            bypassed: None,
+        }));
+        self
+    }
+
+    /// Add a filter step for all channels.
+    pub fn step_filter_all(mut self, names: &[&str]) -> Self {
+        self.pipeline.push(PipelineStep::Filter(PipelineStepFilter {
+            channels: None,
+            names: names.iter().map(|name| name.to_string()).collect(),
+            description: None,
+            bypassed: None,
+        }));
+        self
+    }
+
+    pub fn step_mixer(mut self, name: &str) -> Self {
+        self.pipeline.push(PipelineStep::Mixer(PipelineStepMixer {
+            name: name.to_string(),
+            description: None,
+            bypassed: None,
+        }));
+        self
+    }
+
+    pub fn step_processor(mut self, name: &str) -> Self {
+        self.pipeline
+            .push(PipelineStep::Processor(PipelineStepProcessor {
+                name: name.to_string(),
+                description: None,
+                bypassed: None,
+            }));
+        self
+    }
+
+    pub fn build(self) -> Result<Configuration, Vec<ValidationError>> {
+        let mut errors = self.errors;
+        if self.capture.is_none() {
+            errors.push(ValidationError::new(
+                "devices.capture",
+                ValidationErrorKind::MissingDevice,
+            ));
+        }
+        if self.playback.is_none() {
+            errors.push(ValidationError::new(
+                "devices.playback",
+                ValidationErrorKind::MissingDevice,
+            ));
+        }
+        let (Some(capture), Some(playback)) = (self.capture, self.playback) else {
+            return Err(errors);
+        };
+        let config = Configuration {
+            title: self.title,
+            description: self.description,
+            devices: Devices {
+                samplerate: self.samplerate,
+                chunksize: self.chunksize,
+                queuelimit: self.queuelimit,
+                silence_threshold: self.silence_threshold,
+                silence_timeout: self.silence_timeout,
+                capture,
+                playback,
+                enable_rate_adjust: self.enable_rate_adjust,
+                target_level: self.target_level,
+                adjust_period: self.adjust_period,
+                resampler: self.resampler,
+                capture_samplerate: self.capture_samplerate,
+                stop_on_rate_change: self.stop_on_rate_change,
+                rate_measure_interval: self.rate_measure_interval,
+                volume_ramp_time: self.volume_ramp_time,
+                volume_limit: self.volume_limit,
+                multithreaded: self.multithreaded,
+                worker_threads: self.worker_threads,
+            },
+            mixers: Some(self.mixers).filter(|m| !m.is_empty()),
+            filters: Some(self.filters).filter(|f| !f.is_empty()),
+            processors: Some(self.processors).filter(|p| !p.is_empty()),
+            pipeline: Some(self.pipeline).filter(|p| !p.is_empty()),
+        };
+        if let Err(validation) = config.validate() {
+            errors.extend(validation);
+        }
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capture() -> CaptureDevice {
+        CaptureDevice::CoreAudio(CaptureDeviceCA {
+            channels: 2,
+            device: None,
+            format: None,
+            labels: None,
+        })
+    }
+
+    fn playback() -> PlaybackDevice {
+        PlaybackDevice::CoreAudio(PlaybackDeviceCA {
+            channels: 2,
+            device: None,
+            format: None,
+            exclusive: None,
+        })
+    }
+
+    #[test]
+    fn test_builder_roundtrip() {
+        let config = ConfigurationBuilder::new(48000, 1024)
+            .title("Built")
+            .capture(capture())
+            .playback(playback())
+            .rate_adjust(512)
+            .adjust_period(5.0)
+            .stop_on_rate_change()
+            .rate_measure_interval(2.0)
+            .multithreaded(Some(4))
+            .filter("eq", Filter::peaking(1000.0, 1.0, -3.0))
+            .filter("vol", Filter::gain(-6.0).with_description("Headroom"))
+            .step_filter(&[0, 1], &["eq"])
+            .step_filter_all(&["vol"])
+            .build()
+            .unwrap();
+        assert_eq!(config.devices.adjust_period, Some(5.0));
+        assert_eq!(config.devices.stop_on_rate_change, Some(true));
+        assert_eq!(config.devices.rate_measure_interval, Some(2.0));
+        assert_eq!(config.devices.multithreaded, Some(true));
+        assert_eq!(config.devices.worker_threads, Some(4));
+        assert!(config.mixers.is_none());
+        assert_eq!(config.filters.as_ref().unwrap().len(), 2);
+        assert_eq!(config.pipeline.as_ref().unwrap().len(), 2);
+        let yaml = config.to_yaml_string().unwrap();
+        assert_eq!(Configuration::from_yaml_string(&yaml).unwrap(), config);
+    }
+
+    #[test]
+    fn test_builder_reports_errors() {
+        let errors = ConfigurationBuilder::new(48000, 1024)
+            .playback(playback())
+            .filter("eq", Filter::peaking(1000.0, 1.0, -3.0))
+            .filter("eq", Filter::gain(-3.0))
+            .build()
+            .unwrap_err();
+        let kinds: Vec<_> = errors.iter().map(|e| e.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ValidationErrorKind::DuplicateName("eq".to_string()),
+                ValidationErrorKind::MissingDevice,
+            ]
+        );
+
+        let errors = ConfigurationBuilder::new(48000, 1024)
+            .capture(capture())
+            .playback(playback())
+            .step_filter(&[0, 2], &["missing"])
+            .build()
+            .unwrap_err();
+        let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["pipeline[0].names[0]", "pipeline[0].channels[1]"]
+        );
+    }
+}
@@ -0,0 +1,260 @@
+use std::fmt;
+
+use crate::types::*;
+use crate::validation::{ValidationError, ValidationErrorKind};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepKind {
+    Mixer,
+    Filter,
+    Processor,
+}
+
+impl fmt::Display for StepKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StepKind::Mixer => write!(f, "Mixer"),
+            StepKind::Filter => write!(f, "Filter"),
+            StepKind::Processor => write!(f, "Processor"),
+        }
+    }
+}
+
+/// Channel count before and after one pipeline step.
+/// `None` means the count is unknown, e.g. for a `WavFile` capture device.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StepChannels {
+    pub index: usize,
+    pub kind: StepKind,
+    pub bypassed: bool,
+    pub channels_in: Option<usize>,
+    pub channels_out: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelFlow {
+    pub capture: Option<usize>,
+    pub steps: Vec<StepChannels>,
+    pub playback: usize,
+    pub errors: Vec<ValidationError>,
+}
+
+impl ChannelFlow {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+fn count(channels: Option<usize>) -> String {
+    channels.map_or_else(|| "?".to_string(), |c| c.to_string())
+}
+
+impl fmt::Display for ChannelFlow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "capture: {}", count(self.capture))?;
+        for step in &self.steps {
+            write!(
+                f,
+                "pipeline[{}] {}: {} -> {}",
+                step.index,
+                step.kind,
+                count(step.channels_in),
+                count(step.channels_out)
+            )?;
+            if step.bypassed {
+                write!(f, " (bypassed)")?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "playback: {}", self.playback)
+    }
+}
+
+impl Configuration {
+    /// Trace the number of channels from the capture device through every pipeline step
+    /// to the playback device. Bypassed steps leave the channel count unchanged.
+    pub fn channel_flow(&self) -> ChannelFlow {
+        let capture = self.devices.capture.channels();
+        let playback = self.devices.playback.channels();
+        let mut channels = capture;
+        let mut steps = Vec::new();
+        let mut errors = Vec::new();
+
+        for (idx, step) in self.pipeline.iter().flatten().enumerate() {
+            let bypassed = step.is_bypassed();
+            let channels_in = channels;
+            let kind = match step {
+                PipelineStep::Mixer(step) => {
+                    if !bypassed {
+                        let mixer = self.mixers.as_ref().and_then(|m| m.get(&step.name));
+                        if let (Some(mixer), Some(found)) = (mixer, channels) {
+                            if mixer.channels.r#in != found {
+                                errors.push(ValidationError::new(
+                                    format!("pipeline[{}]", idx),
+                                    ValidationErrorKind::MixerInputMismatch {
+                                        expected: mixer.channels.r#in,
+                                        found,
+                                    },
+                                ));
+                            }
+                        }
+                        // An unknown mixer is reported by the reference checks,
+                        // after it the channel count is unknown.
+                        channels = mixer.map(|m| m.channels.out);
+                    }
+                    StepKind::Mixer
+                }
+                PipelineStep::Filter(step) => {
+                    if let (Some(found), Some(used)) = (channels, &step.channels) {
+                        for (n, channel) in used.iter().enumerate() {
+                            if *channel >= found {
+                                errors.push(ValidationError::new(
+                                    format!("pipeline[{}].channels[{}]", idx, n),
+                                    ValidationErrorKind::FilterChannelOutOfRange {
+                                        channel: *channel,
+                                        channels: found,
+                                    },
+                                ));
+                            }
+                        }
+                    }
+                    StepKind::Filter
+                }
+                PipelineStep::Processor(step) => {
+                    let processor = self.processors.as_ref().and_then(|p| p.get(&step.name));
+                    if let (false, Some(processor), Some(found)) = (bypassed, processor, channels) {
+                        let expected = processor.channels();
+                        if expected != found {
+                            errors.push(ValidationError::new(
+                                format!("pipeline[{}]", idx),
+                                ValidationErrorKind::ProcessorChannelMismatch { expected, found },
+                            ));
+                        }
+                    }
+                    StepKind::Processor
+                }
+            };
+            steps.push(StepChannels {
+                index: idx,
+                kind,
+                bypassed,
+                channels_in,
+                channels_out: channels,
+            });
+        }
+
+        if let Some(found) = channels {
+            if found != playback {
+                errors.push(ValidationError::new(
+                    "devices.playback.channels",
+                    ValidationErrorKind::PlaybackChannelMismatch {
+                        expected: playback,
+                        found,
+                    },
+                ));
+            }
+        }
+
+        ChannelFlow {
+            capture,
+            steps,
+            playback,
+            errors,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_flow_table_and_errors() {
+        let yaml = r#"---
+devices:
+  samplerate: 48000
+  chunksize: 1024
+  capture:
+    type: Stdin
+    channels: 2
+    format: S16_LE
+  playback:
+    type: Stdout
+    channels: 2
+    format: S16_LE
+filters:
+  vol:
+    type: Gain
+    parameters:
+      gain: -3
+mixers:
+  to4:
+    channels:
+      in: 2
+      out: 4
+    mapping:
+      - dest: 0
+        sources:
+          - channel: 0
+      - dest: 1
+        sources:
+          - channel: 1
+      - dest: 2
+        sources:
+          - channel: 0
+      - dest: 3
+        sources:
+          - channel: 1
+processors:
+  comp:
+    type: Compressor
+    parameters:
+      channels: 2
+      attack: 0.025
+      release: 1.0
+      threshold: -25
+      factor: 5.0
+pipeline:
+  - type: Filter
+    channels: [0, 1]
+    names: [vol]
+  - type: Mixer
+    name: to4
+  - type: Processor
+    name: comp
+  - type: Filter
+    channels: [3, 4]
+    names: [vol]
+  - type: Mixer
+    name: to4
+    bypassed: true
+"#;
+        let config = Configuration::from_yaml_string(yaml).unwrap();
+        let flow = config.channel_flow();
+        let counts: Vec<_> = flow
+            .steps
+            .iter()
+            .map(|s| (s.channels_in, s.channels_out))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                (Some(2), Some(2)),
+                (Some(2), Some(4)),
+                (Some(4), Some(4)),
+                (Some(4), Some(4)),
+                (Some(4), Some(4)),
+            ]
+        );
+        let errors: Vec<String> = flow.errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            errors,
+            vec![
+                "pipeline[2]: processor expects 2 channels but 4 channels exist here",
+                "pipeline[3].channels[1]: filter step uses channel 4 but only 4 channels exist here",
+                "devices.playback.channels: playback device has 2 channels but the pipeline ends with 4",
+            ]
+        );
+        assert!(config.validate().is_err());
+    }
+}
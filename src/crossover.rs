@@ -0,0 +1,345 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::dsp::ComboError;
+use crate::types::*;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CrossoverError {
+    NoDrivers,
+    DuplicateDriver(String),
+    NotHighpass(String),
+    NotLowpass(String),
+    InvalidCombo { driver: String, error: ComboError },
+}
+
+impl fmt::Display for CrossoverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrossoverError::NoDrivers => write!(f, "a crossover needs at least one driver"),
+            CrossoverError::DuplicateDriver(name) => {
+                write!(f, "driver '{}' is defined more than once", name)
+            }
+            CrossoverError::NotHighpass(name) => {
+                write!(f, "highpass of driver '{}' is not a highpass filter", name)
+            }
+            CrossoverError::NotLowpass(name) => {
+                write!(f, "lowpass of driver '{}' is not a lowpass filter", name)
+            }
+            CrossoverError::InvalidCombo { driver, error } => {
+                write!(f, "driver '{}': {}", driver, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CrossoverError {}
+
+/// One driver of an active speaker, fed from a band of the input signal.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Driver {
+    pub name: String,
+    pub highpass: Option<BiquadComboParameters>,
+    pub lowpass: Option<BiquadComboParameters>,
+    /// Gain in dB.
+    pub gain: f64,
+    /// Delay in milliseconds.
+    pub delay: f64,
+    pub inverted: bool,
+}
+
+impl Driver {
+    pub fn new(name: impl Into<String>) -> Self {
+        Driver {
+            name: name.into(),
+            highpass: None,
+            lowpass: None,
+            gain: 0.0,
+            delay: 0.0,
+            inverted: false,
+        }
+    }
+
+    pub fn highpass(mut self, parameters: BiquadComboParameters) -> Self {
+        self.highpass = Some(parameters);
+        self
+    }
+
+    pub fn lowpass(mut self, parameters: BiquadComboParameters) -> Self {
+        self.lowpass = Some(parameters);
+        self
+    }
+
+    pub fn gain(mut self, gain: f64) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    pub fn delay(mut self, delay: f64) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn inverted(mut self) -> Self {
+        self.inverted = true;
+        self
+    }
+
+    fn check(&self) -> Result<(), CrossoverError> {
+        if let Some(highpass) = &self.highpass {
+            if !matches!(
+                highpass,
+                BiquadComboParameters::LinkwitzRileyHighpass { .. }
+                    | BiquadComboParameters::ButterworthHighpass { .. }
+            ) {
+                return Err(CrossoverError::NotHighpass(self.name.clone()));
+            }
+        }
+        if let Some(lowpass) = &self.lowpass {
+            if !matches!(
+                lowpass,
+                BiquadComboParameters::LinkwitzRileyLowpass { .. }
+                    | BiquadComboParameters::ButterworthLowpass { .. }
+            ) {
+                return Err(CrossoverError::NotLowpass(self.name.clone()));
+            }
+        }
+        for parameters in self.highpass.iter().chain(&self.lowpass) {
+            parameters
+                .expand()
+                .map_err(|error| CrossoverError::InvalidCombo {
+                    driver: self.name.clone(),
+                    error,
+                })?;
+        }
+        Ok(())
+    }
+}
+
+/// Mixer, filters and pipeline steps of a stereo N-way crossover.
+/// Output channels are grouped per driver, left then right:
+/// driver 0 uses channels 0 and 1, driver 1 uses 2 and 3, and so on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Crossover {
+    pub mixer: (String, Mixer),
+    pub filters: Vec<(String, Filter)>,
+    pub steps: Vec<PipelineStep>,
+}
+
+impl Crossover {
+    /// Generate a crossover splitting a stereo input over the given drivers.
+    /// The mixer is called `name`, the filters of each driver are called
+    /// `<driver>_highpass`, `<driver>_lowpass`, `<driver>_gain` and `<driver>_delay`.
+    /// Gain and delay filters are only added when needed.
+    pub fn new(name: &str, drivers: &[Driver]) -> Result<Self, CrossoverError> {
+        if drivers.is_empty() {
+            return Err(CrossoverError::NoDrivers);
+        }
+        let mut seen = HashSet::new();
+        for driver in drivers {
+            if !seen.insert(driver.name.as_str()) {
+                return Err(CrossoverError::DuplicateDriver(driver.name.clone()));
+            }
+            driver.check()?;
+        }
+
+        let mut mapping = Vec::new();
+        let mut labels = Vec::new();
+        for (idx, driver) in drivers.iter().enumerate() {
+            for (side, label) in ["L", "R"].iter().enumerate() {
+                mapping.push(MixerMapping {
+                    dest: 2 * idx + side,
+                    sources: vec![MixerSource {
+                        channel: side,
+                        gain: Some(0.0),
+                        inverted: None,
+                        mute: None,
+                        scale: Some(GainScale::Decibel),
+                    }],
+                    mute: None,
+                });
+                labels.push(Some(format!("{} {}", driver.name, label)));
+            }
+        }
+        let mixer = Mixer {
+            description: Some(format!("{}-way crossover", drivers.len())),
+            channels: MixerChannels {
+                r#in: 2,
+                out: 2 * drivers.len(),
+            },
+            mapping,
+            labels: Some(labels),
+        };
+
+        let mut filters = Vec::new();
+        let mut steps = vec![PipelineStep::Mixer(PipelineStepMixer {
+            name: name.to_string(),
+            description: None,
+            bypassed: None,
+        })];
+        for (idx, driver) in drivers.iter().enumerate() {
+            let mut driver_filters = Vec::new();
+            if let Some(highpass) = &driver.highpass {
+                driver_filters.push(("highpass", Filter::biquad_combo(highpass.clone())));
+            }
+            if let Some(lowpass) = &driver.lowpass {
+                driver_filters.push(("lowpass", Filter::biquad_combo(lowpass.clone())));
+            }
+            if driver.gain != 0.0 || driver.inverted {
+                let mut gain = Filter::gain(driver.gain);
+                if let Filter::Gain { parameters, .. } = &mut gain {
+                    parameters.inverted = Some(driver.inverted);
+                }
+                driver_filters.push(("gain", gain));
+            }
+            if driver.delay != 0.0 {
+                driver_filters.push(("delay", Filter::delay(driver.delay, TimeUnit::Milliseconds)));
+            }
+            if driver_filters.is_empty() {
+                continue;
+            }
+            let names: Vec<String> = driver_filters
+                .iter()
+                .map(|(suffix, _)| format!("{}_{}", driver.name, suffix))
+                .collect();
+            steps.push(PipelineStep::Filter(PipelineStepFilter {
+                channels: Some(vec![2 * idx, 2 * idx + 1]),
+                names: names.clone(),
+                description: Some(driver.name.clone()),
+                bypassed: None,
+            }));
+            filters.extend(
+                names
+                    .into_iter()
+                    .zip(driver_filters.into_iter().map(|(_, f)| f)),
+            );
+        }
+
+        Ok(Crossover {
+            mixer: (name.to_string(), mixer),
+            filters,
+            steps,
+        })
+    }
+
+    /// Add the mixer and filters to the configuration and append the steps to the pipeline.
+    /// Existing mixers and filters with the same names are replaced.
+    pub fn add_to(&self, config: &mut Configuration) {
+        let (name, mixer) = &self.mixer;
+        config
+            .mixers
+            .get_or_insert_with(Default::default)
+            .insert(name.clone(), mixer.clone());
+        let filters = config.filters.get_or_insert_with(Default::default);
+        for (name, filter) in &self.filters {
+            filters.insert(name.clone(), filter.clone());
+        }
+        config
+            .pipeline
+            .get_or_insert_with(Vec::new)
+            .extend(self.steps.iter().cloned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(playback_channels: usize) -> Configuration {
+        Configuration::from_yaml_string(&format!(
+            r#"---
+devices:
+  samplerate: 48000
+  chunksize: 1024
+  capture:
+    type: Stdin
+    channels: 2
+    format: S16_LE
+  playback:
+    type: Stdout
+    channels: {}
+    format: S16_LE
+"#,
+            playback_channels
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_two_way_crossover() {
+        let drivers = [
+            Driver::new("woofer").lowpass(BiquadComboParameters::LinkwitzRileyLowpass {
+                freq: 2000.0,
+                order: 4,
+            }),
+            Driver::new("tweeter")
+                .highpass(BiquadComboParameters::LinkwitzRileyHighpass {
+                    freq: 2000.0,
+                    order: 4,
+                })
+                .gain(-3.0)
+                .delay(0.1),
+        ];
+        let crossover = Crossover::new("split", &drivers).unwrap();
+        assert_eq!(crossover.mixer.1.channels.out, 4);
+        let names: Vec<&str> = crossover.filters.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "woofer_lowpass",
+                "tweeter_highpass",
+                "tweeter_gain",
+                "tweeter_delay"
+            ]
+        );
+        assert_eq!(crossover.steps.len(), 3);
+
+        let mut config = config(4);
+        crossover.add_to(&mut config);
+        assert_eq!(config.validate(), Ok(()));
+
+        // Without gain and delay, an LR4 woofer and tweeter sum to a flat magnitude.
+        let flat = Crossover::new(
+            "split",
+            &[
+                drivers[0].clone(),
+                Driver::new("tweeter").highpass(BiquadComboParameters::LinkwitzRileyHighpass {
+                    freq: 2000.0,
+                    order: 4,
+                }),
+            ],
+        )
+        .unwrap();
+        let mut config = self::config(4);
+        flat.add_to(&mut config);
+        let freqs = [100.0, 1000.0, 2000.0, 4000.0, 15000.0];
+        let response = config.frequency_response(&freqs).unwrap();
+        for n in 0..freqs.len() {
+            let sum = response.path(0, 0).values[n] + response.path(2, 0).values[n];
+            assert!((sum.norm() - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_crossover_errors() {
+        let lowpass = BiquadComboParameters::LinkwitzRileyLowpass {
+            freq: 500.0,
+            order: 3,
+        };
+        assert_eq!(
+            Crossover::new("x", &[Driver::new("sub").lowpass(lowpass.clone())]),
+            Err(CrossoverError::InvalidCombo {
+                driver: "sub".to_string(),
+                error: ComboError::OddLinkwitzRileyOrder(3)
+            })
+        );
+        assert_eq!(
+            Crossover::new("x", &[Driver::new("sub").highpass(lowpass)]),
+            Err(CrossoverError::NotHighpass("sub".to_string()))
+        );
+        assert_eq!(
+            Crossover::new("x", &[Driver::new("a"), Driver::new("a")]),
+            Err(CrossoverError::DuplicateDriver("a".to_string()))
+        );
+    }
+}
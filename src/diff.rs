@@ -0,0 +1,325 @@
+use std::fmt;
+
+use serde_yaml::Value;
+
+use crate::edit::{path_string, render_flow, Segment};
+use crate::types::Configuration;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// One difference between two configs. Unset optional fields count as absent.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change {
+    pub path: String,
+    /// The keys and indices that `path` is rendered from.
+    pub segments: Vec<Segment>,
+    pub kind: ChangeKind,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let render = |value: &Option<Value>| value.as_ref().map(render_flow).unwrap_or_default();
+        match self.kind {
+            ChangeKind::Added => write!(f, "+ {}: {}", self.path, render(&self.new)),
+            ChangeKind::Removed => write!(f, "- {}: {}", self.path, render(&self.old)),
+            ChangeKind::Changed => write!(
+                f,
+                "~ {}: {} -> {}",
+                self.path,
+                render(&self.old),
+                render(&self.new)
+            ),
+        }
+    }
+}
+
+/// What a running CamillaDSP has to do to apply a new config,
+/// from least to most disruptive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Impact {
+    /// Nothing that affects processing changed, for example only the title.
+    None,
+    /// Parameters of existing filters, mixers or processors changed
+    /// and can be updated while running.
+    Parameters,
+    /// The pipeline has to be rebuilt, the devices keep running.
+    Pipeline,
+    /// Device settings changed, processing has to be restarted.
+    Restart,
+}
+
+impl fmt::Display for Impact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Impact::None => "no effect on processing",
+            Impact::Parameters => "parameters can be updated in place",
+            Impact::Pipeline => "pipeline must be rebuilt",
+            Impact::Restart => "processing must be restarted",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConfigDiff {
+    pub changes: Vec<Change>,
+}
+
+impl ConfigDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn impact(&self) -> Impact {
+        self.changes
+            .iter()
+            .map(change_impact)
+            .max()
+            .unwrap_or(Impact::None)
+    }
+
+    pub fn needs_restart(&self) -> bool {
+        self.impact() == Impact::Restart
+    }
+}
+
+impl fmt::Display for ConfigDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no changes");
+        }
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        writeln!(f, "{} change(s), {}", self.changes.len(), self.impact())
+    }
+}
+
+fn change_impact(change: &Change) -> Impact {
+    let key = |n: usize| match change.segments.get(n) {
+        Some(Segment::Key(key)) => Some(key.as_str()),
+        _ => None,
+    };
+    // Items are keyed by name, the field follows the name.
+    let field = key(2);
+    match key(0).unwrap_or_default() {
+        "devices" => Impact::Restart,
+        "pipeline" => Impact::Pipeline,
+        "filters" | "processors" => match (change.kind, field) {
+            (ChangeKind::Changed, Some("parameters")) => Impact::Parameters,
+            (ChangeKind::Changed, Some("description")) => Impact::None,
+            _ => Impact::Pipeline,
+        },
+        "mixers" => match (change.kind, field) {
+            (ChangeKind::Changed, Some("mapping")) => Impact::Parameters,
+            (ChangeKind::Changed, Some("description" | "labels")) => Impact::None,
+            _ => Impact::Pipeline,
+        },
+        _ => Impact::None,
+    }
+}
+
+impl Configuration {
+    /// Compare two configs. Filters, mixers and processors are matched by name,
+    /// pipeline steps by position.
+    pub fn diff(&self, new: &Configuration) -> ConfigDiff {
+        let old = serde_yaml::to_value(self).unwrap_or(Value::Null);
+        let new = serde_yaml::to_value(new).unwrap_or(Value::Null);
+        let mut changes = Vec::new();
+        compare(&[], Some(&old), Some(&new), &mut changes);
+        ConfigDiff { changes }
+    }
+}
+
+fn present(value: Option<&Value>) -> Option<&Value> {
+    value.filter(|v| !v.is_null())
+}
+
+fn child(path: &[Segment], segment: Segment) -> Vec<Segment> {
+    let mut path = path.to_vec();
+    path.push(segment);
+    path
+}
+
+fn compare(path: &[Segment], old: Option<&Value>, new: Option<&Value>, changes: &mut Vec<Change>) {
+    let (old, new) = match (present(old), present(new)) {
+        (None, None) => return,
+        (Some(old), Some(new)) if old == new => return,
+        (None, Some(new)) => {
+            changes.push(Change {
+                path: path_string(path),
+                segments: path.to_vec(),
+                kind: ChangeKind::Added,
+                old: None,
+                new: Some(new.clone()),
+            });
+            return;
+        }
+        (Some(old), None) => {
+            changes.push(Change {
+                path: path_string(path),
+                segments: path.to_vec(),
+                kind: ChangeKind::Removed,
+                old: Some(old.clone()),
+                new: None,
+            });
+            return;
+        }
+        (Some(old), Some(new)) => (old, new),
+    };
+    match (old, new) {
+        // A different `type` is a different kind of item, report it as a whole.
+        (Value::Mapping(a), Value::Mapping(b)) if a.get("type") == b.get("type") => {
+            for (key, value) in a {
+                let key_text = key.as_str().unwrap_or_default();
+                compare(
+                    &child(path, key_text.into()),
+                    Some(value),
+                    b.get(key),
+                    changes,
+                );
+            }
+            for (key, value) in b {
+                if !a.contains_key(key) {
+                    let key_text = key.as_str().unwrap_or_default();
+                    compare(&child(path, key_text.into()), None, Some(value), changes);
+                }
+            }
+        }
+        (Value::Sequence(a), Value::Sequence(b))
+            if a.iter().chain(b).all(|item| item.is_mapping()) =>
+        {
+            for idx in 0..a.len().max(b.len()) {
+                compare(&child(path, idx.into()), a.get(idx), b.get(idx), changes);
+            }
+        }
+        _ => changes.push(Change {
+            path: path_string(path),
+            segments: path.to_vec(),
+            kind: ChangeKind::Changed,
+            old: Some(old.clone()),
+            new: Some(new.clone()),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = r#"---
+title: Living room
+devices:
+  samplerate: 48000
+  chunksize: 1024
+  capture:
+    type: Stdin
+    channels: 2
+    format: S16_LE
+  playback:
+    type: Stdout
+    channels: 2
+    format: S16_LE
+filters:
+  eq:
+    type: Biquad
+    parameters:
+      type: Peaking
+      freq: 1000
+      q: 1.0
+      gain: -3
+  vol:
+    type: Gain
+    parameters:
+      gain: -6
+pipeline:
+  - type: Filter
+    names: [eq, vol]
+"#;
+
+    fn config(yaml: &str) -> Configuration {
+        Configuration::from_yaml_string(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_parameter_changes() {
+        let old = config(BASE);
+        assert!(old.diff(&old).is_empty());
+        assert_eq!(old.diff(&old).impact(), Impact::None);
+
+        let new = config(
+            &BASE
+                .replace("gain: -3", "gain: -2")
+                .replace("Living room", "Kitchen"),
+        );
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff.changes
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "~ title: Living room -> Kitchen",
+                "~ filters.eq.parameters.gain: -3.0 -> -2.0",
+            ]
+        );
+        assert_eq!(diff.impact(), Impact::Parameters);
+        assert!(!diff.needs_restart());
+
+        let new = config(&BASE.replace("type: Peaking", "type: Highshelf"));
+        let diff = old.diff(&new);
+        assert_eq!(diff.changes.len(), 1);
+        assert_eq!(diff.changes[0].path, "filters.eq.parameters");
+
+        // A dot in a name does not change how the change is classified.
+        let dotted = BASE
+            .replace("  eq:", "  eq.v2:")
+            .replace("[eq, vol]", "[\"eq.v2\", vol]");
+        let diff = config(&dotted).diff(&config(&dotted.replace("gain: -3", "gain: -2")));
+        assert_eq!(diff.changes[0].path, "filters.eq.v2.parameters.gain");
+        assert_eq!(diff.impact(), Impact::Parameters);
+    }
+
+    #[test]
+    fn test_structural_changes() {
+        let old = config(BASE);
+        let new = config(
+            &BASE
+                .replace("chunksize: 1024", "chunksize: 2048")
+                .replace(
+                    "  vol:\n    type: Gain\n    parameters:\n      gain: -6\n",
+                    "",
+                )
+                .replace(
+                    "names: [eq, vol]",
+                    "names: [eq]\n  - type: Filter\n    names: [eq]",
+                ),
+        );
+        let diff = old.diff(&new);
+        let kinds: Vec<(&str, ChangeKind)> = diff
+            .changes
+            .iter()
+            .map(|c| (c.path.as_str(), c.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("devices.chunksize", ChangeKind::Changed),
+                ("filters.vol", ChangeKind::Removed),
+                ("pipeline[0].names", ChangeKind::Changed),
+                ("pipeline[1]", ChangeKind::Added),
+            ]
+        );
+        assert!(diff.needs_restart());
+        let text = diff.to_string();
+        assert!(text.contains("~ pipeline[0].names: [eq, vol] -> [eq]\n"));
+        assert!(text.ends_with("4 change(s), processing must be restarted\n"));
+    }
+}
@@ -0,0 +1,366 @@
+use std::f64::consts::PI;
+
+use crate::types::{
+    BiquadParameters, GeneralNotchParams, NotchWidth, PeakingWidth, ShelfSteepness,
+};
+
+/// Normalized biquad coefficients, with a0 = 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BiquadCoefficients {
+    pub b0: f64,
+    pub b1: f64,
+    pub b2: f64,
+    pub a1: f64,
+    pub a2: f64,
+}
+
+impl BiquadCoefficients {
+    pub fn new(b0: f64, b1: f64, b2: f64, a1: f64, a2: f64) -> Self {
+        BiquadCoefficients { b0, b1, b2, a1, a2 }
+    }
+
+    fn normalized(b0: f64, b1: f64, b2: f64, a0: f64, a1: f64, a2: f64) -> Self {
+        BiquadCoefficients::new(b0 / a0, b1 / a0, b2 / a0, a1 / a0, a2 / a0)
+    }
+
+    /// Check that both poles are inside the unit circle.
+    pub fn is_stable(&self) -> bool {
+        self.a2.abs() < 1.0 && self.a1.abs() < 1.0 + self.a2
+    }
+
+    /// Calculate the coefficients the same way as CamillaDSP does.
+    pub fn from_config(samplerate: usize, parameters: &BiquadParameters) -> Self {
+        let fs = samplerate as f64;
+        match *parameters {
+            BiquadParameters::Free { a1, a2, b0, b1, b2 } => {
+                BiquadCoefficients::new(b0, b1, b2, a1, a2)
+            }
+            BiquadParameters::Highpass { freq, q } => {
+                let (sn, cs) = sin_cos(freq, fs);
+                let alpha = sn / (2.0 * q);
+                let b0 = (1.0 + cs) / 2.0;
+                let b1 = -(1.0 + cs);
+                let b2 = (1.0 + cs) / 2.0;
+                let a0 = 1.0 + alpha;
+                let a1 = -2.0 * cs;
+                let a2 = 1.0 - alpha;
+                BiquadCoefficients::normalized(b0, b1, b2, a0, a1, a2)
+            }
+            BiquadParameters::Lowpass { freq, q } => {
+                let (sn, cs) = sin_cos(freq, fs);
+                let alpha = sn / (2.0 * q);
+                let b0 = (1.0 - cs) / 2.0;
+                let b1 = 1.0 - cs;
+                let b2 = (1.0 - cs) / 2.0;
+                let a0 = 1.0 + alpha;
+                let a1 = -2.0 * cs;
+                let a2 = 1.0 - alpha;
+                BiquadCoefficients::normalized(b0, b1, b2, a0, a1, a2)
+            }
+            BiquadParameters::HighpassFO { freq } => {
+                let k = (omega(freq, fs) / 2.0).tan();
+                let alpha = 1.0 + k;
+                let a1 = -((1.0 - k) / alpha);
+                let b0 = 1.0 / alpha;
+                let b1 = -1.0 / alpha;
+                BiquadCoefficients::new(b0, b1, 0.0, a1, 0.0)
+            }
+            BiquadParameters::LowpassFO { freq } => {
+                let k = (omega(freq, fs) / 2.0).tan();
+                let alpha = 1.0 + k;
+                let a1 = -((1.0 - k) / alpha);
+                let b0 = k / alpha;
+                let b1 = k / alpha;
+                BiquadCoefficients::new(b0, b1, 0.0, a1, 0.0)
+            }
+            BiquadParameters::Peaking(ref width) => {
+                let (freq, gain, alpha) = match *width {
+                    PeakingWidth::Q { freq, q, gain } => {
+                        let (sn, _) = sin_cos(freq, fs);
+                        (freq, gain, sn / (2.0 * q))
+                    }
+                    PeakingWidth::Bandwidth {
+                        freq,
+                        bandwidth,
+                        gain,
+                    } => (freq, gain, bandwidth_alpha(freq, fs, bandwidth)),
+                };
+                let (_, cs) = sin_cos(freq, fs);
+                let ampl = 10.0_f64.powf(gain / 40.0);
+                let b0 = 1.0 + alpha * ampl;
+                let b1 = -2.0 * cs;
+                let b2 = 1.0 - alpha * ampl;
+                let a0 = 1.0 + alpha / ampl;
+                let a1 = -2.0 * cs;
+                let a2 = 1.0 - alpha / ampl;
+                BiquadCoefficients::normalized(b0, b1, b2, a0, a1, a2)
+            }
+            BiquadParameters::Highshelf(ref steepness) => {
+                let (freq, ampl, alpha) = shelf_alpha(steepness, fs);
+                let (_, cs) = sin_cos(freq, fs);
+                let sq = 2.0 * ampl.sqrt() * alpha;
+                let b0 = ampl * ((ampl + 1.0) + (ampl - 1.0) * cs + sq);
+                let b1 = -2.0 * ampl * ((ampl - 1.0) + (ampl + 1.0) * cs);
+                let b2 = ampl * ((ampl + 1.0) + (ampl - 1.0) * cs - sq);
+                let a0 = (ampl + 1.0) - (ampl - 1.0) * cs + sq;
+                let a1 = 2.0 * ((ampl - 1.0) - (ampl + 1.0) * cs);
+                let a2 = (ampl + 1.0) - (ampl - 1.0) * cs - sq;
+                BiquadCoefficients::normalized(b0, b1, b2, a0, a1, a2)
+            }
+            BiquadParameters::Lowshelf(ref steepness) => {
+                let (freq, ampl, alpha) = shelf_alpha(steepness, fs);
+                let (_, cs) = sin_cos(freq, fs);
+                let sq = 2.0 * ampl.sqrt() * alpha;
+                let b0 = ampl * ((ampl + 1.0) - (ampl - 1.0) * cs + sq);
+                let b1 = 2.0 * ampl * ((ampl - 1.0) - (ampl + 1.0) * cs);
+                let b2 = ampl * ((ampl + 1.0) - (ampl - 1.0) * cs - sq);
+                let a0 = (ampl + 1.0) + (ampl - 1.0) * cs + sq;
+                let a1 = -2.0 * ((ampl - 1.0) + (ampl + 1.0) * cs);
+                let a2 = (ampl + 1.0) + (ampl - 1.0) * cs - sq;
+                BiquadCoefficients::normalized(b0, b1, b2, a0, a1, a2)
+            }
+            BiquadParameters::HighshelfFO { freq, gain } => {
+                let tn = (omega(freq, fs) / 2.0).tan();
+                let ampl = 10.0_f64.powf(gain / 40.0);
+                let b0 = ampl * tn + ampl.powi(2);
+                let b1 = ampl * tn - ampl.powi(2);
+                let a0 = ampl * tn + 1.0;
+                let a1 = ampl * tn - 1.0;
+                BiquadCoefficients::normalized(b0, b1, 0.0, a0, a1, 0.0)
+            }
+            BiquadParameters::LowshelfFO { freq, gain } => {
+                let tn = (omega(freq, fs) / 2.0).tan();
+                let ampl = 10.0_f64.powf(gain / 40.0);
+                let b0 = ampl.powi(2) * tn + ampl;
+                let b1 = ampl.powi(2) * tn - ampl;
+                let a0 = tn + ampl;
+                let a1 = tn - ampl;
+                BiquadCoefficients::normalized(b0, b1, 0.0, a0, a1, 0.0)
+            }
+            BiquadParameters::Allpass(ref width) => {
+                let (freq, alpha) = notch_alpha(width, fs);
+                let (_, cs) = sin_cos(freq, fs);
+                let b0 = 1.0 - alpha;
+                let b1 = -2.0 * cs;
+                let b2 = 1.0 + alpha;
+                let a0 = 1.0 + alpha;
+                let a1 = -2.0 * cs;
+                let a2 = 1.0 - alpha;
+                BiquadCoefficients::normalized(b0, b1, b2, a0, a1, a2)
+            }
+            BiquadParameters::AllpassFO { freq } => {
+                let tn = (omega(freq, fs) / 2.0).tan();
+                let c = (tn - 1.0) / (tn + 1.0);
+                BiquadCoefficients::new(c, 1.0, 0.0, c, 0.0)
+            }
+            BiquadParameters::Bandpass(ref width) => {
+                let (freq, alpha) = notch_alpha(width, fs);
+                let (_, cs) = sin_cos(freq, fs);
+                let b0 = alpha;
+                let b1 = 0.0;
+                let b2 = -alpha;
+                let a0 = 1.0 + alpha;
+                let a1 = -2.0 * cs;
+                let a2 = 1.0 - alpha;
+                BiquadCoefficients::normalized(b0, b1, b2, a0, a1, a2)
+            }
+            BiquadParameters::Notch(ref width) => {
+                let (freq, alpha) = notch_alpha(width, fs);
+                let (_, cs) = sin_cos(freq, fs);
+                let b0 = 1.0;
+                let b1 = -2.0 * cs;
+                let b2 = 1.0;
+                let a0 = 1.0 + alpha;
+                let a1 = -2.0 * cs;
+                let a2 = 1.0 - alpha;
+                BiquadCoefficients::normalized(b0, b1, b2, a0, a1, a2)
+            }
+            BiquadParameters::GeneralNotch(GeneralNotchParams {
+                freq_p,
+                freq_z,
+                q_p,
+                normalize_at_dc,
+            }) => {
+                let tn_z = (PI * freq_z / fs).tan();
+                let tn_p = (PI * freq_p / fs).tan();
+                let alpha_p = tn_p / q_p;
+                let tn2_p = tn_p.powi(2);
+                let tn2_z = tn_z.powi(2);
+                // Unity gain at Nyquist by default, optionally at DC instead.
+                let gain = if normalize_at_dc.unwrap_or(false) {
+                    tn2_p / tn2_z
+                } else {
+                    1.0
+                };
+                let b0 = gain * (1.0 + tn2_z);
+                let b1 = -2.0 * gain * (1.0 - tn2_z);
+                let b2 = gain * (1.0 + tn2_z);
+                let a0 = 1.0 + alpha_p + tn2_p;
+                let a1 = -2.0 + 2.0 * tn2_p;
+                let a2 = 1.0 - alpha_p + tn2_p;
+                BiquadCoefficients::normalized(b0, b1, b2, a0, a1, a2)
+            }
+            BiquadParameters::LinkwitzTransform {
+                freq_act,
+                q_act,
+                freq_target,
+                q_target,
+            } => {
+                let d0i = (2.0 * PI * freq_act).powi(2);
+                let d1i = (2.0 * PI * freq_act) / q_act;
+                let c0i = (2.0 * PI * freq_target).powi(2);
+                let c1i = (2.0 * PI * freq_target) / q_target;
+                let fc = (freq_target + freq_act) / 2.0;
+                let gn = 2.0 * PI * fc / (PI * fc / fs).tan();
+                let cci = c0i + gn * c1i + gn.powi(2);
+                let b0 = (d0i + gn * d1i + gn.powi(2)) / cci;
+                let b1 = 2.0 * (d0i - gn.powi(2)) / cci;
+                let b2 = (d0i - gn * d1i + gn.powi(2)) / cci;
+                let a1 = 2.0 * (c0i - gn.powi(2)) / cci;
+                let a2 = (c0i - gn * c1i + gn.powi(2)) / cci;
+                BiquadCoefficients::new(b0, b1, b2, a1, a2)
+            }
+        }
+    }
+}
+
+fn omega(freq: f64, fs: f64) -> f64 {
+    2.0 * PI * freq / fs
+}
+
+fn sin_cos(freq: f64, fs: f64) -> (f64, f64) {
+    omega(freq, fs).sin_cos()
+}
+
+fn bandwidth_alpha(freq: f64, fs: f64, bandwidth: f64) -> f64 {
+    let omega = omega(freq, fs);
+    let sn = omega.sin();
+    sn * ((2.0_f64).ln() / 2.0 * bandwidth * omega / sn).sinh()
+}
+
+fn notch_alpha(width: &NotchWidth, fs: f64) -> (f64, f64) {
+    match *width {
+        NotchWidth::Q { freq, q } => (freq, sin_cos(freq, fs).0 / (2.0 * q)),
+        NotchWidth::Bandwidth { freq, bandwidth } => (freq, bandwidth_alpha(freq, fs, bandwidth)),
+    }
+}
+
+// Returns (freq, amplitude, alpha). The slope is given in dB/octave, where 12 dB/octave
+// corresponds to the cookbook shelf slope S = 1.
+fn shelf_alpha(steepness: &ShelfSteepness, fs: f64) -> (f64, f64, f64) {
+    match *steepness {
+        ShelfSteepness::Q { freq, q, gain } => {
+            let ampl = 10.0_f64.powf(gain / 40.0);
+            (freq, ampl, sin_cos(freq, fs).0 / (2.0 * q))
+        }
+        ShelfSteepness::Slope { freq, slope, gain } => {
+            let ampl = 10.0_f64.powf(gain / 40.0);
+            let sn = sin_cos(freq, fs).0;
+            let alpha =
+                sn / 2.0 * ((ampl + 1.0 / ampl) * (1.0 / (slope / 12.0) - 1.0) + 2.0).sqrt();
+            (freq, ampl, alpha)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gain_at(coeffs: &BiquadCoefficients, freq: f64, fs: f64) -> f64 {
+        // |H(e^jw)| evaluated directly from the transfer function.
+        let w = 2.0 * PI * freq / fs;
+        let (re_n, im_n) = (
+            coeffs.b0 + coeffs.b1 * w.cos() + coeffs.b2 * (2.0 * w).cos(),
+            -coeffs.b1 * w.sin() - coeffs.b2 * (2.0 * w).sin(),
+        );
+        let (re_d, im_d) = (
+            1.0 + coeffs.a1 * w.cos() + coeffs.a2 * (2.0 * w).cos(),
+            -coeffs.a1 * w.sin() - coeffs.a2 * (2.0 * w).sin(),
+        );
+        20.0 * ((re_n.powi(2) + im_n.powi(2)) / (re_d.powi(2) + im_d.powi(2)))
+            .sqrt()
+            .log10()
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-6, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_lowpass_matches_cookbook() {
+        let coeffs = BiquadCoefficients::from_config(
+            44100,
+            &BiquadParameters::Lowpass {
+                freq: 1000.0,
+                q: 0.707,
+            },
+        );
+        assert_close(coeffs.b0, 0.004_603_935_028_493_071);
+        assert_close(coeffs.b1, 0.009_207_870_056_986_141);
+        assert_close(coeffs.a1, -1.799_071_616_595_651);
+        assert_close(coeffs.a2, 0.817_487_356_709_623);
+        assert!(coeffs.is_stable());
+    }
+
+    #[test]
+    fn test_gains_at_key_frequencies() {
+        let fs = 48000.0;
+        let peak = BiquadCoefficients::from_config(
+            48000,
+            &BiquadParameters::Peaking(PeakingWidth::Q {
+                freq: 1000.0,
+                q: 2.0,
+                gain: -6.0,
+            }),
+        );
+        assert_close(gain_at(&peak, 1000.0, fs), -6.0);
+
+        let shelf = BiquadCoefficients::from_config(
+            48000,
+            &BiquadParameters::Lowshelf(ShelfSteepness::Slope {
+                freq: 100.0,
+                slope: 6.0,
+                gain: 4.0,
+            }),
+        );
+        assert_close(gain_at(&shelf, 0.0, fs), 4.0);
+        assert_close(gain_at(&shelf, 100.0, fs), 2.0);
+
+        let shelf_fo = BiquadCoefficients::from_config(
+            48000,
+            &BiquadParameters::HighshelfFO {
+                freq: 2000.0,
+                gain: 3.0,
+            },
+        );
+        assert_close(gain_at(&shelf_fo, 0.0, fs), 0.0);
+        assert_close(gain_at(&shelf_fo, 24000.0, fs), 3.0);
+
+        let notch = BiquadCoefficients::from_config(
+            48000,
+            &BiquadParameters::GeneralNotch(GeneralNotchParams {
+                freq_p: 100.0,
+                freq_z: 200.0,
+                q_p: 0.7,
+                normalize_at_dc: Some(true),
+            }),
+        );
+        assert_close(gain_at(&notch, 0.0, fs), 0.0);
+
+        let allpass =
+            BiquadCoefficients::from_config(48000, &BiquadParameters::AllpassFO { freq: 500.0 });
+        assert_close(gain_at(&allpass, 1234.0, fs), 0.0);
+
+        let lt = BiquadCoefficients::from_config(
+            48000,
+            &BiquadParameters::LinkwitzTransform {
+                freq_act: 50.0,
+                q_act: 0.7,
+                freq_target: 25.0,
+                q_target: 0.7,
+            },
+        );
+        assert_close(gain_at(&lt, 0.0, fs), 20.0 * (4.0_f64).log10());
+    }
+}
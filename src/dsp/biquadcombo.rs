@@ -0,0 +1,320 @@
+use std::f64::consts::PI;
+use std::fmt;
+
+use crate::types::{
+    BiquadComboParameters, BiquadParameters, GraphicEqualizerParameters, PeakingWidth,
+    ShelfSteepness,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ComboError {
+    ZeroOrder,
+    OddLinkwitzRileyOrder(usize),
+    NoBands,
+    InvalidFrequencyRange { freq_min: f64, freq_max: f64 },
+}
+
+impl fmt::Display for ComboError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComboError::ZeroOrder => write!(f, "filter order must be larger than zero"),
+            ComboError::OddLinkwitzRileyOrder(order) => write!(
+                f,
+                "Linkwitz-Riley order must be an even number, got {}",
+                order
+            ),
+            ComboError::NoBands => write!(f, "graphic equalizer must have at least one band"),
+            ComboError::InvalidFrequencyRange { freq_min, freq_max } => write!(
+                f,
+                "freq_min ({}) must be positive and lower than freq_max ({})",
+                freq_min, freq_max
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ComboError {}
+
+// Q values of the second order sections of a Butterworth filter.
+// A negative value marks the first order section of an odd order filter.
+fn butterworth_q(order: usize) -> Vec<f64> {
+    let mut qvalues = Vec::new();
+    for i in 0..order / 2 {
+        qvalues.push(1.0 / (2.0 * (PI / order as f64 * (i as f64 + 0.5)).sin()));
+    }
+    if !order.is_multiple_of(2) {
+        qvalues.push(-1.0);
+    }
+    qvalues
+}
+
+// A Linkwitz-Riley filter is two cascaded Butterworth filters of half the order.
+// Two first order sections are merged into one second order section with Q = 0.5.
+fn linkwitz_riley_q(order: usize) -> Vec<f64> {
+    let mut q_half = butterworth_q(order / 2);
+    let mut qvalues = Vec::new();
+    if !(order / 2).is_multiple_of(2) {
+        q_half.pop();
+        qvalues.extend_from_slice(&q_half);
+        qvalues.extend_from_slice(&q_half);
+        qvalues.push(0.5);
+    } else {
+        qvalues.extend_from_slice(&q_half);
+        qvalues.extend_from_slice(&q_half);
+    }
+    qvalues
+}
+
+fn highpass_sections(freq: f64, qvalues: Vec<f64>) -> Vec<BiquadParameters> {
+    qvalues
+        .into_iter()
+        .map(|q| {
+            if q < 0.0 {
+                BiquadParameters::HighpassFO { freq }
+            } else {
+                BiquadParameters::Highpass { freq, q }
+            }
+        })
+        .collect()
+}
+
+fn lowpass_sections(freq: f64, qvalues: Vec<f64>) -> Vec<BiquadParameters> {
+    qvalues
+        .into_iter()
+        .map(|q| {
+            if q < 0.0 {
+                BiquadParameters::LowpassFO { freq }
+            } else {
+                BiquadParameters::Lowpass { freq, q }
+            }
+        })
+        .collect()
+}
+
+fn check_butterworth_order(order: usize) -> Result<usize, ComboError> {
+    if order == 0 {
+        return Err(ComboError::ZeroOrder);
+    }
+    Ok(order)
+}
+
+fn check_linkwitz_riley_order(order: usize) -> Result<usize, ComboError> {
+    if order == 0 {
+        return Err(ComboError::ZeroOrder);
+    }
+    if !order.is_multiple_of(2) {
+        return Err(ComboError::OddLinkwitzRileyOrder(order));
+    }
+    Ok(order)
+}
+
+impl GraphicEqualizerParameters {
+    pub const DEFAULT_FREQ_MIN: f32 = 20.0;
+    pub const DEFAULT_FREQ_MAX: f32 = 20000.0;
+
+    fn range(&self) -> (f64, f64) {
+        (
+            self.freq_min.unwrap_or(Self::DEFAULT_FREQ_MIN) as f64,
+            self.freq_max.unwrap_or(Self::DEFAULT_FREQ_MAX) as f64,
+        )
+    }
+
+    /// Width of each band in octaves.
+    pub fn bandwidth(&self) -> f64 {
+        let (freq_min, freq_max) = self.range();
+        (freq_max.log2() - freq_min.log2()) / self.gains.len() as f64
+    }
+
+    /// Center frequencies of the bands, spaced logarithmically between
+    /// `freq_min` and `freq_max` with one band per entry in `gains`.
+    pub fn band_frequencies(&self) -> Vec<f64> {
+        let (freq_min, _) = self.range();
+        let bandwidth = self.bandwidth();
+        (0..self.gains.len())
+            .map(|band| 2.0_f64.powf(freq_min.log2() + (band as f64 + 0.5) * bandwidth))
+            .collect()
+    }
+
+    fn sections(&self) -> Result<Vec<BiquadParameters>, ComboError> {
+        let (freq_min, freq_max) = self.range();
+        if self.gains.is_empty() {
+            return Err(ComboError::NoBands);
+        }
+        if freq_min <= 0.0 || freq_min >= freq_max {
+            return Err(ComboError::InvalidFrequencyRange { freq_min, freq_max });
+        }
+        let bandwidth = self.bandwidth();
+        Ok(self
+            .band_frequencies()
+            .into_iter()
+            .zip(&self.gains)
+            // Bands without gain are left out, they would not change the response.
+            .filter(|(_, gain)| gain.abs() > 0.01)
+            .map(|(freq, gain)| {
+                BiquadParameters::Peaking(PeakingWidth::Bandwidth {
+                    freq,
+                    bandwidth,
+                    gain: *gain as f64,
+                })
+            })
+            .collect())
+    }
+}
+
+impl BiquadComboParameters {
+    /// List the biquads that CamillaDSP cascades to implement this filter.
+    pub fn expand(&self) -> Result<Vec<BiquadParameters>, ComboError> {
+        let sections = match *self {
+            BiquadComboParameters::LinkwitzRileyHighpass { freq, order } => {
+                highpass_sections(freq, linkwitz_riley_q(check_linkwitz_riley_order(order)?))
+            }
+            BiquadComboParameters::LinkwitzRileyLowpass { freq, order } => {
+                lowpass_sections(freq, linkwitz_riley_q(check_linkwitz_riley_order(order)?))
+            }
+            BiquadComboParameters::ButterworthHighpass { freq, order } => {
+                highpass_sections(freq, butterworth_q(check_butterworth_order(order)?))
+            }
+            BiquadComboParameters::ButterworthLowpass { freq, order } => {
+                lowpass_sections(freq, butterworth_q(check_butterworth_order(order)?))
+            }
+            BiquadComboParameters::Tilt { gain } => vec![
+                BiquadParameters::LowshelfFO {
+                    freq: 110.0,
+                    gain: -gain / 2.0,
+                },
+                BiquadParameters::HighshelfFO {
+                    freq: 3500.0,
+                    gain: gain / 2.0,
+                },
+            ],
+            BiquadComboParameters::FivePointPeq {
+                fls,
+                qls,
+                gls,
+                fp1,
+                qp1,
+                gp1,
+                fp2,
+                qp2,
+                gp2,
+                fp3,
+                qp3,
+                gp3,
+                fhs,
+                qhs,
+                ghs,
+            } => vec![
+                BiquadParameters::Lowshelf(ShelfSteepness::Q {
+                    freq: fls,
+                    q: qls,
+                    gain: gls,
+                }),
+                BiquadParameters::Peaking(PeakingWidth::Q {
+                    freq: fp1,
+                    q: qp1,
+                    gain: gp1,
+                }),
+                BiquadParameters::Peaking(PeakingWidth::Q {
+                    freq: fp2,
+                    q: qp2,
+                    gain: gp2,
+                }),
+                BiquadParameters::Peaking(PeakingWidth::Q {
+                    freq: fp3,
+                    q: qp3,
+                    gain: gp3,
+                }),
+                BiquadParameters::Highshelf(ShelfSteepness::Q {
+                    freq: fhs,
+                    q: qhs,
+                    gain: ghs,
+                }),
+            ],
+            BiquadComboParameters::GraphicEqualizer(ref parameters) => parameters.sections()?,
+        };
+        Ok(sections)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn qvalues(sections: &[BiquadParameters]) -> Vec<f64> {
+        sections
+            .iter()
+            .map(|s| match s {
+                BiquadParameters::Highpass { q, .. } | BiquadParameters::Lowpass { q, .. } => {
+                    (q * 1000.0).round() / 1000.0
+                }
+                BiquadParameters::HighpassFO { .. } | BiquadParameters::LowpassFO { .. } => -1.0,
+                _ => panic!("unexpected section {:?}", s),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_expand_crossover_filters() {
+        let lr4 = BiquadComboParameters::LinkwitzRileyLowpass {
+            freq: 2000.0,
+            order: 4,
+        };
+        assert_eq!(qvalues(&lr4.expand().unwrap()), vec![0.707, 0.707]);
+
+        let lr6 = BiquadComboParameters::LinkwitzRileyHighpass {
+            freq: 2000.0,
+            order: 6,
+        };
+        assert_eq!(qvalues(&lr6.expand().unwrap()), vec![1.0, 1.0, 0.5]);
+
+        let bw5 = BiquadComboParameters::ButterworthHighpass {
+            freq: 80.0,
+            order: 5,
+        };
+        assert_eq!(qvalues(&bw5.expand().unwrap()), vec![1.618, 0.618, -1.0]);
+
+        let lr3 = BiquadComboParameters::LinkwitzRileyLowpass {
+            freq: 2000.0,
+            order: 3,
+        };
+        assert_eq!(lr3.expand(), Err(ComboError::OddLinkwitzRileyOrder(3)));
+        let bw0 = BiquadComboParameters::ButterworthLowpass {
+            freq: 2000.0,
+            order: 0,
+        };
+        assert_eq!(bw0.expand(), Err(ComboError::ZeroOrder));
+    }
+
+    #[test]
+    fn test_expand_graphic_equalizer() {
+        let params = GraphicEqualizerParameters {
+            freq_min: Some(100.0),
+            freq_max: Some(1600.0),
+            gains: vec![1.0, 0.0, -2.0, 3.0],
+        };
+        let freqs = params.band_frequencies();
+        let expected = [141.421, 282.843, 565.685, 1131.371];
+        for (f, e) in freqs.iter().zip(expected) {
+            assert!((f - e).abs() < 1e-3);
+        }
+        let sections = BiquadComboParameters::GraphicEqualizer(params)
+            .expand()
+            .unwrap();
+        assert_eq!(sections.len(), 3);
+        assert!(matches!(
+            sections[1],
+            BiquadParameters::Peaking(PeakingWidth::Bandwidth { bandwidth, gain, .. })
+                if (bandwidth - 1.0).abs() < 1e-9 && gain == -2.0
+        ));
+
+        let empty = GraphicEqualizerParameters {
+            freq_min: None,
+            freq_max: None,
+            gains: vec![],
+        };
+        assert_eq!(
+            BiquadComboParameters::GraphicEqualizer(empty).expand(),
+            Err(ComboError::NoBands)
+        );
+    }
+}
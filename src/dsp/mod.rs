@@ -0,0 +1,7 @@
+mod biquad;
+mod biquadcombo;
+mod response;
+
+pub use biquad::*;
+pub use biquadcombo::*;
+pub use response::*;
@@ -0,0 +1,456 @@
+use std::f64::consts::PI;
+use std::fmt;
+
+use num_complex::Complex64;
+
+use super::biquad::BiquadCoefficients;
+use super::biquadcombo::ComboError;
+use crate::types::*;
+use crate::validation::ValidationError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ResponseError {
+    UnsupportedFilter(String),
+    InvalidCombo(ComboError),
+    UnknownChannelCount,
+    InvalidConfig(Vec<ValidationError>),
+}
+
+impl fmt::Display for ResponseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResponseError::UnsupportedFilter(reason) => {
+                write!(f, "unsupported filter: {}", reason)
+            }
+            ResponseError::InvalidCombo(error) => write!(f, "invalid combo filter: {}", error),
+            ResponseError::UnknownChannelCount => {
+                write!(f, "the number of capture channels is not known")
+            }
+            ResponseError::InvalidConfig(errors) => {
+                write!(f, "invalid config")?;
+                for error in errors {
+                    write!(f, "\n  {}", error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ResponseError {}
+
+/// Complex response evaluated at a list of frequencies.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrequencyResponse {
+    pub freqs: Vec<f64>,
+    pub values: Vec<Complex64>,
+}
+
+impl FrequencyResponse {
+    fn unity(freqs: &[f64]) -> Self {
+        FrequencyResponse::constant(freqs, Complex64::new(1.0, 0.0))
+    }
+
+    fn constant(freqs: &[f64], value: Complex64) -> Self {
+        FrequencyResponse {
+            freqs: freqs.to_vec(),
+            values: vec![value; freqs.len()],
+        }
+    }
+
+    pub fn magnitude_db(&self) -> Vec<f64> {
+        self.values
+            .iter()
+            .map(|v| 20.0 * v.norm().log10())
+            .collect()
+    }
+
+    pub fn phase_deg(&self) -> Vec<f64> {
+        self.values.iter().map(|v| v.arg().to_degrees()).collect()
+    }
+
+    fn multiply(&mut self, other: &FrequencyResponse) {
+        for (v, o) in self.values.iter_mut().zip(&other.values) {
+            *v *= o;
+        }
+    }
+}
+
+// z^-1 on the unit circle for each frequency.
+fn z_inv(freqs: &[f64], samplerate: usize) -> Vec<Complex64> {
+    freqs
+        .iter()
+        .map(|f| Complex64::from_polar(1.0, -2.0 * PI * f / samplerate as f64))
+        .collect()
+}
+
+fn polynomial(coeffs: &[f64], z_inv: Complex64) -> Complex64 {
+    coeffs
+        .iter()
+        .rev()
+        .fold(Complex64::new(0.0, 0.0), |acc, c| acc * z_inv + c)
+}
+
+fn delay_response(freqs: &[f64], samplerate: usize, samples: f64) -> FrequencyResponse {
+    FrequencyResponse {
+        freqs: freqs.to_vec(),
+        values: freqs
+            .iter()
+            .map(|f| Complex64::from_polar(1.0, -2.0 * PI * f * samples / samplerate as f64))
+            .collect(),
+    }
+}
+
+fn delay_samples(delay: f64, unit: TimeUnit, subsample: bool, samplerate: usize) -> f64 {
+    let samples = unit.to_samples(delay, samplerate);
+    if subsample {
+        samples
+    } else {
+        samples.round()
+    }
+}
+
+impl BiquadCoefficients {
+    pub fn response(&self, samplerate: usize, freqs: &[f64]) -> FrequencyResponse {
+        let values = z_inv(freqs, samplerate)
+            .into_iter()
+            .map(|z| {
+                polynomial(&[self.b0, self.b1, self.b2], z)
+                    / polynomial(&[1.0, self.a1, self.a2], z)
+            })
+            .collect();
+        FrequencyResponse {
+            freqs: freqs.to_vec(),
+            values,
+        }
+    }
+}
+
+impl Filter {
+    /// Evaluate the response of the filter at the given frequencies.
+    /// Level dependent filters (Volume, Loudness, Dither, Limiter) are evaluated
+    /// as unity gain, and convolution filters only support inline values.
+    pub fn response(
+        &self,
+        samplerate: usize,
+        freqs: &[f64],
+    ) -> Result<FrequencyResponse, ResponseError> {
+        let response = match self {
+            Filter::Biquad { parameters, .. } => {
+                BiquadCoefficients::from_config(samplerate, parameters).response(samplerate, freqs)
+            }
+            Filter::BiquadCombo { parameters, .. } => {
+                let mut response = FrequencyResponse::unity(freqs);
+                for biquad in parameters.expand().map_err(ResponseError::InvalidCombo)? {
+                    response.multiply(
+                        &BiquadCoefficients::from_config(samplerate, &biquad)
+                            .response(samplerate, freqs),
+                    );
+                }
+                response
+            }
+            Filter::Gain { parameters, .. } => {
+                FrequencyResponse::constant(freqs, Complex64::new(parameters.linear_gain(), 0.0))
+            }
+            Filter::Delay { parameters, .. } => delay_response(
+                freqs,
+                samplerate,
+                delay_samples(
+                    parameters.delay,
+                    parameters.unit.unwrap_or(TimeUnit::Milliseconds),
+                    parameters.subsample.unwrap_or(false),
+                    samplerate,
+                ),
+            ),
+            Filter::DiffEq { parameters, .. } => {
+                let (a, b) = parameters
+                    .coefficients()
+                    .map_err(ResponseError::UnsupportedFilter)?;
+                FrequencyResponse {
+                    freqs: freqs.to_vec(),
+                    values: z_inv(freqs, samplerate)
+                        .into_iter()
+                        .map(|z| polynomial(&b, z) / polynomial(&a, z))
+                        .collect(),
+                }
+            }
+            Filter::Conv { parameters, .. } => match parameters {
+                ConvParameters::Values { values } => FrequencyResponse {
+                    freqs: freqs.to_vec(),
+                    values: z_inv(freqs, samplerate)
+                        .into_iter()
+                        .map(|z| polynomial(values, z))
+                        .collect(),
+                },
+                ConvParameters::Dummy { .. } => FrequencyResponse::unity(freqs),
+                ConvParameters::Raw(ConvParametersRaw { filename, .. })
+                | ConvParameters::Wav(ConvParametersWav { filename, .. }) => {
+                    return Err(ResponseError::UnsupportedFilter(format!(
+                        "convolution coefficients are read from file '{}'",
+                        filename
+                    )));
+                }
+            },
+            Filter::Volume { .. }
+            | Filter::Loudness { .. }
+            | Filter::Dither { .. }
+            | Filter::Limiter { .. } => FrequencyResponse::unity(freqs),
+        };
+        Ok(response)
+    }
+}
+
+/// Responses of every path through the pipeline, indexed as `paths[output][input]`,
+/// where inputs are capture channels and outputs are playback channels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PipelineResponse {
+    pub freqs: Vec<f64>,
+    pub paths: Vec<Vec<FrequencyResponse>>,
+}
+
+impl PipelineResponse {
+    pub fn path(&self, output: usize, input: usize) -> &FrequencyResponse {
+        &self.paths[output][input]
+    }
+}
+
+type Paths = Vec<Vec<Vec<Complex64>>>;
+
+fn apply_race(paths: &mut Paths, parameters: &RACEParameters, freqs: &[f64], samplerate: usize) {
+    let samples = delay_samples(
+        parameters.delay,
+        parameters.delay_unit.unwrap_or(TimeUnit::Milliseconds),
+        parameters.subsample_delay.unwrap_or(false),
+        samplerate,
+    );
+    let gain = 10.0_f64.powf(-parameters.attenuation / 20.0);
+    let (a, b) = (parameters.channel_a, parameters.channel_b);
+    // Each output feeds the other through an inverted, attenuated delay:
+    // Ya = Xa - g D Yb, Yb = Xb - g D Ya
+    let delay = delay_response(freqs, samplerate, samples);
+    for input in 0..paths[a].len() {
+        for (f, d) in delay.values.iter().enumerate() {
+            let gd = gain * d;
+            let xa = paths[a][input][f];
+            let xb = paths[b][input][f];
+            let det = Complex64::new(1.0, 0.0) - gd * gd;
+            paths[a][input][f] = (xa - gd * xb) / det;
+            paths[b][input][f] = (xb - gd * xa) / det;
+        }
+    }
+}
+
+impl Configuration {
+    /// Evaluate the linear response from every capture channel to every playback channel.
+    /// Compressors and noise gates are assumed to be idle and pass the signal unchanged.
+    pub fn frequency_response(&self, freqs: &[f64]) -> Result<PipelineResponse, ResponseError> {
+        self.validate().map_err(ResponseError::InvalidConfig)?;
+        let samplerate = self.devices.samplerate;
+        let inputs = self
+            .devices
+            .capture
+            .channels()
+            .ok_or(ResponseError::UnknownChannelCount)?;
+
+        let zero = vec![Complex64::new(0.0, 0.0); freqs.len()];
+        let one = vec![Complex64::new(1.0, 0.0); freqs.len()];
+        let mut paths: Paths = (0..inputs)
+            .map(|out| {
+                (0..inputs)
+                    .map(|inp| {
+                        if inp == out {
+                            one.clone()
+                        } else {
+                            zero.clone()
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        for step in self.pipeline.iter().flatten() {
+            if step.is_bypassed() {
+                continue;
+            }
+            match step {
+                PipelineStep::Filter(step) => {
+                    let channels: Vec<usize> = match &step.channels {
+                        Some(channels) => channels.clone(),
+                        None => (0..paths.len()).collect(),
+                    };
+                    for name in &step.names {
+                        // The reference was checked by validate.
+                        let filter = &self.filters.as_ref().unwrap()[name];
+                        let response = filter.response(samplerate, freqs)?;
+                        for channel in &channels {
+                            for path in paths[*channel].iter_mut() {
+                                for (v, r) in path.iter_mut().zip(&response.values) {
+                                    *v *= r;
+                                }
+                            }
+                        }
+                    }
+                }
+                PipelineStep::Mixer(step) => {
+                    let mixer = &self.mixers.as_ref().unwrap()[&step.name];
+                    let mut mixed = vec![vec![zero.clone(); inputs]; mixer.channels.out];
+                    for mapping in &mixer.mapping {
+                        if mapping.mute.unwrap_or(false) {
+                            continue;
+                        }
+                        for source in &mapping.sources {
+                            let gain = source.linear_gain();
+                            for (input, path) in paths[source.channel].iter().enumerate() {
+                                for (m, v) in mixed[mapping.dest][input].iter_mut().zip(path) {
+                                    *m += v * gain;
+                                }
+                            }
+                        }
+                    }
+                    paths = mixed;
+                }
+                PipelineStep::Processor(step) => {
+                    let processor = &self.processors.as_ref().unwrap()[&step.name];
+                    if let Processor::RACE { parameters, .. } = processor {
+                        apply_race(&mut paths, parameters, freqs, samplerate);
+                    }
+                }
+            }
+        }
+
+        let paths = paths
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|values| FrequencyResponse {
+                        freqs: freqs.to_vec(),
+                        values,
+                    })
+                    .collect()
+            })
+            .collect();
+        Ok(PipelineResponse {
+            freqs: freqs.to_vec(),
+            paths,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-6, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_filter_responses() {
+        let freqs = [100.0, 1000.0, 10000.0];
+        let gain = Filter::Gain {
+            description: None,
+            parameters: GainParameters {
+                gain: -6.0,
+                inverted: Some(true),
+                mute: None,
+                scale: None,
+            },
+        };
+        let response = gain.response(48000, &freqs).unwrap();
+        assert_close(response.magnitude_db()[1], -6.0);
+        assert_close(response.phase_deg()[1].abs(), 180.0);
+
+        let delay = Filter::Delay {
+            description: None,
+            parameters: DelayParameters {
+                delay: 12.0,
+                unit: Some(TimeUnit::Samples),
+                subsample: None,
+            },
+        };
+        let response = delay.response(48000, &freqs).unwrap();
+        // 12 samples at 1 kHz and 48 kHz is a quarter period.
+        assert_close(response.phase_deg()[1], -90.0);
+
+        let lr = Filter::BiquadCombo {
+            description: None,
+            parameters: BiquadComboParameters::LinkwitzRileyLowpass {
+                freq: 1000.0,
+                order: 4,
+            },
+        };
+        let response = lr.response(48000, &freqs).unwrap();
+        assert_close(response.magnitude_db()[1], -6.020_599_913_279_624);
+
+        let fir = Filter::Conv {
+            description: None,
+            parameters: ConvParameters::Values {
+                values: vec![0.5, 0.5],
+            },
+        };
+        let response = fir.response(48000, &[0.0, 24000.0]).unwrap();
+        assert_close(response.values[0].norm(), 1.0);
+        assert_close(response.values[1].norm(), 0.0);
+
+        let diffeq = Filter::DiffEq {
+            description: None,
+            parameters: DiffEqParameters {
+                a: Some(vec![]),
+                b: None,
+            },
+        };
+        assert_eq!(
+            diffeq.response(48000, &freqs),
+            Err(ResponseError::UnsupportedFilter(
+                "DiffEq needs a non-zero a0 coefficient".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_pipeline_response_with_mixer() {
+        let yaml = r#"---
+devices:
+  samplerate: 48000
+  chunksize: 1024
+  capture:
+    type: Stdin
+    channels: 2
+    format: S16_LE
+  playback:
+    type: Stdout
+    channels: 1
+    format: S16_LE
+filters:
+  atten:
+    type: Gain
+    parameters:
+      gain: -6
+mixers:
+  mono:
+    channels:
+      in: 2
+      out: 1
+    mapping:
+      - dest: 0
+        sources:
+          - channel: 0
+            gain: 0
+          - channel: 1
+            gain: 0.5
+            scale: linear
+            inverted: true
+pipeline:
+  - type: Filter
+    channels: [0]
+    names: [atten]
+  - type: Mixer
+    name: mono
+"#;
+        let config = Configuration::from_yaml_string(yaml).unwrap();
+        let response = config.frequency_response(&[1000.0]).unwrap();
+        assert_eq!(response.paths.len(), 1);
+        assert_close(response.path(0, 0).magnitude_db()[0], -6.0);
+        assert_close(response.path(0, 1).values[0].re, -0.5);
+    }
+}
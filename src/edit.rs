@@ -0,0 +1,988 @@
+use std::fmt;
+
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+
+use crate::types::*;
+
+/// One step of a path into a YAML document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+impl From<&str> for Segment {
+    fn from(key: &str) -> Self {
+        Segment::Key(key.to_string())
+    }
+}
+
+impl From<usize> for Segment {
+    fn from(index: usize) -> Self {
+        Segment::Index(index)
+    }
+}
+
+pub(crate) fn path_string(path: &[Segment]) -> String {
+    let mut out = String::new();
+    for segment in path {
+        match segment {
+            Segment::Key(key) if out.is_empty() => out.push_str(key),
+            Segment::Key(key) => {
+                out.push('.');
+                out.push_str(key);
+            }
+            Segment::Index(index) => out.push_str(&format!("[{}]", index)),
+        }
+    }
+    out
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EditError {
+    /// The document is not a valid config, or an edit would make it invalid.
+    Invalid(String),
+    NotFound(String),
+    AlreadyExists(String),
+    /// The value at the path is written in a style that can not be edited in place,
+    /// for example a flow mapping.
+    NotEditable(String),
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::Invalid(reason) => write!(f, "invalid config: {}", reason),
+            EditError::NotFound(path) => write!(f, "{}: not found", path),
+            EditError::AlreadyExists(path) => write!(f, "{}: already exists", path),
+            EditError::NotEditable(path) => write!(f, "{}: can not be edited in place", path),
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+// --- Line scanning ---
+
+#[derive(Clone, Copy, Debug)]
+struct Line {
+    // Byte offset of the line in the document.
+    start: usize,
+    // Length without the line break.
+    len: usize,
+    indent: usize,
+    // End of the content, before any comment and trailing whitespace.
+    end: usize,
+}
+
+impl Line {
+    fn is_content(&self) -> bool {
+        self.end > self.indent
+    }
+}
+
+fn scan_lines(text: &str) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for raw in text.split_inclusive('\n') {
+        let line = raw.trim_end_matches(['\n', '\r']);
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        let mut end = content_end(line);
+        let content = &line[indent..end.max(indent)];
+        if indent == 0 && (content == "---" || content == "..." || content.starts_with('%')) {
+            end = 0;
+        }
+        lines.push(Line {
+            start,
+            len: line.len(),
+            indent,
+            end: end.max(indent),
+        });
+        start += raw.len();
+    }
+    lines
+}
+
+fn content_end(line: &str) -> usize {
+    let mut single = false;
+    let mut double = false;
+    let mut prev = ' ';
+    let mut end = line.len();
+    for (idx, c) in line.char_indices() {
+        // A quote only starts a quoted scalar at the start of the scalar,
+        // an apostrophe inside a plain scalar is just a character.
+        // A quote right after a closing single quote is the escape `''`.
+        let scalar_start = prev.is_whitespace() || matches!(prev, '[' | '{' | ',');
+        match c {
+            '\'' if single => single = false,
+            '\'' if !double && (scalar_start || prev == '\'') => single = true,
+            '"' if double && prev != '\\' => double = false,
+            '"' if !single && scalar_start => double = true,
+            '#' if !single && !double && prev.is_whitespace() => {
+                end = idx;
+                break;
+            }
+            _ => {}
+        }
+        prev = c;
+    }
+    line[..end].trim_end().len()
+}
+
+// Position of the colon ending a mapping key at the start of `text`.
+fn key_end(text: &str) -> Option<usize> {
+    let first = text.chars().next()?;
+    if matches!(first, '[' | '{' | '#' | '|' | '>' | '&' | '*' | '!')
+        || text == "-"
+        || text.starts_with("- ")
+    {
+        return None;
+    }
+    let search_from = if first == '"' || first == '\'' {
+        let close = text[1..].find(first)? + 1;
+        close + 1
+    } else {
+        0
+    };
+    let bytes = text.as_bytes();
+    (search_from..bytes.len())
+        .find(|&idx| bytes[idx] == b':' && (idx + 1 == bytes.len() || bytes[idx + 1] == b' '))
+}
+
+fn is_dash(text: &str) -> bool {
+    text == "-" || text.starts_with("- ")
+}
+
+// --- Located nodes ---
+
+#[derive(Clone, Copy, Debug)]
+enum Node {
+    // A value written on the line of its key or dash, possibly continued on
+    // the following lines up to `end` (exclusive).
+    Inline {
+        line: usize,
+        start: usize,
+        stop: usize,
+        end: usize,
+    },
+    // A block mapping or sequence whose content starts at column `col` of line `start`.
+    Block {
+        start: usize,
+        col: usize,
+        end: usize,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    line: usize,
+    col: usize,
+    // Column just after the colon or the dash.
+    after: usize,
+    end: usize,
+    value: Node,
+}
+
+/// A CamillaDSP config kept as the original YAML text.
+/// Edits change only the affected lines, so comments, key order and
+/// quoting elsewhere in the document are kept as written.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigDocument {
+    text: String,
+}
+
+impl ConfigDocument {
+    pub fn new(text: impl Into<String>) -> Result<Self, EditError> {
+        let document = ConfigDocument { text: text.into() };
+        document.configuration()?;
+        Ok(document)
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn into_string(self) -> String {
+        self.text
+    }
+
+    pub fn configuration(&self) -> Result<Configuration, EditError> {
+        Configuration::from_yaml_string(&self.text).map_err(|e| EditError::Invalid(e.to_string()))
+    }
+
+    // --- Typed edits ---
+
+    /// Set the `gain` parameter of a filter that has one.
+    pub fn set_filter_gain(&mut self, name: &str, gain: f64) -> Result<(), EditError> {
+        let path = [
+            Segment::from("filters"),
+            name.into(),
+            "parameters".into(),
+            "gain".into(),
+        ];
+        if self.value_at(&path).is_none() {
+            return Err(EditError::NotFound(path_string(&path)));
+        }
+        self.set_value(&path, &gain)
+    }
+
+    /// Replace the definition of an existing filter.
+    pub fn set_filter(&mut self, name: &str, filter: &Filter) -> Result<(), EditError> {
+        let path = [Segment::from("filters"), name.into()];
+        if self.value_at(&path).is_none() {
+            return Err(EditError::NotFound(path_string(&path)));
+        }
+        self.set_value(&path, filter)
+    }
+
+    pub fn add_filter(&mut self, name: &str, filter: &Filter) -> Result<(), EditError> {
+        self.add_named("filters", name, filter)
+    }
+
+    pub fn add_mixer(&mut self, name: &str, mixer: &Mixer) -> Result<(), EditError> {
+        self.add_named("mixers", name, mixer)
+    }
+
+    pub fn add_processor(&mut self, name: &str, processor: &Processor) -> Result<(), EditError> {
+        self.add_named("processors", name, processor)
+    }
+
+    pub fn add_pipeline_step(&mut self, step: &PipelineStep) -> Result<(), EditError> {
+        self.push_value(&["pipeline".into()], step)
+    }
+
+    pub fn remove_pipeline_step(&mut self, index: usize) -> Result<(), EditError> {
+        self.remove(&["pipeline".into(), index.into()])
+    }
+
+    pub fn set_step_bypassed(&mut self, index: usize, bypassed: bool) -> Result<(), EditError> {
+        let step = [Segment::from("pipeline"), index.into()];
+        if self.value_at(&step).is_none() {
+            return Err(EditError::NotFound(path_string(&step)));
+        }
+        self.set_value(
+            &[step[0].clone(), step[1].clone(), "bypassed".into()],
+            &bypassed,
+        )
+    }
+
+    fn add_named<T: Serialize>(
+        &mut self,
+        section: &str,
+        name: &str,
+        value: &T,
+    ) -> Result<(), EditError> {
+        let path = [Segment::from(section), name.into()];
+        if self.value_at(&path).is_some() {
+            return Err(EditError::AlreadyExists(path_string(&path)));
+        }
+        self.set_value(&path, value)
+    }
+
+    // --- Generic edits ---
+
+    /// Set the value at `path`, adding the key and any missing parent mappings.
+    pub fn set_value<T: Serialize>(
+        &mut self,
+        path: &[Segment],
+        value: &T,
+    ) -> Result<(), EditError> {
+        let value = to_value(value)?;
+        self.checked(|doc| doc.set(path, value))
+    }
+
+    /// Append an item to the sequence at `path`, creating it if needed.
+    pub fn push_value<T: Serialize>(
+        &mut self,
+        path: &[Segment],
+        value: &T,
+    ) -> Result<(), EditError> {
+        let value = to_value(value)?;
+        self.checked(|doc| doc.push(path, value))
+    }
+
+    /// Remove a mapping entry or a sequence item.
+    pub fn remove(&mut self, path: &[Segment]) -> Result<(), EditError> {
+        self.checked(|doc| {
+            let lines = scan_lines(&doc.text);
+            let (parent, last) = path
+                .split_last()
+                .map(|(last, parent)| (parent, last))
+                .ok_or_else(|| EditError::NotFound(String::new()))?;
+            let node = doc.find(&lines, parent)?;
+            let entry = doc
+                .child(&lines, node, last)
+                .ok_or_else(|| EditError::NotFound(path_string(path)))?;
+            if lines[entry.line].indent != entry.col {
+                // The first key of a sequence item shares its line with the dash.
+                return Err(EditError::NotEditable(path_string(path)));
+            }
+            let from = lines[entry.line].start;
+            let to = lines.get(entry.end).map_or(doc.text.len(), |l| l.start);
+            doc.text.replace_range(from..to, "");
+            Ok(())
+        })
+    }
+
+    // Apply an edit and keep it only if the result is still a valid config.
+    fn checked(
+        &mut self,
+        edit: impl FnOnce(&mut Self) -> Result<(), EditError>,
+    ) -> Result<(), EditError> {
+        let original = self.text.clone();
+        let result = edit(self).and_then(|_| self.configuration().map(|_| ()));
+        if result.is_err() {
+            self.text = original;
+        }
+        result
+    }
+
+    fn value_at(&self, path: &[Segment]) -> Option<Value> {
+        let mut value: Value = serde_yaml::from_str(&self.text).ok()?;
+        for segment in path {
+            value = match segment {
+                Segment::Key(key) => value.as_mapping_mut()?.remove(key.as_str())?,
+                Segment::Index(index) => {
+                    let items = value.as_sequence_mut()?;
+                    if *index >= items.len() {
+                        return None;
+                    }
+                    items.swap_remove(*index)
+                }
+            };
+        }
+        Some(value)
+    }
+
+    fn indent_unit(&self, lines: &[Line]) -> usize {
+        lines
+            .iter()
+            .filter(|l| l.is_content() && l.indent > 0)
+            .map(|l| l.indent)
+            .min()
+            .unwrap_or(2)
+    }
+
+    fn line_text(&self, line: &Line) -> &str {
+        &self.text[line.start..line.start + line.len]
+    }
+
+    fn content(&self, line: &Line, col: usize) -> &str {
+        &self.text[line.start + col..line.start + line.end]
+    }
+
+    fn root(&self, lines: &[Line]) -> Option<Node> {
+        let start = lines.iter().position(|l| l.is_content())?;
+        Some(Node::Block {
+            start,
+            col: lines[start].indent,
+            end: trim_end(lines, lines.len()),
+        })
+    }
+
+    fn find(&self, lines: &[Line], path: &[Segment]) -> Result<Node, EditError> {
+        let mut node = self
+            .root(lines)
+            .ok_or_else(|| EditError::NotFound(path_string(path)))?;
+        for (depth, segment) in path.iter().enumerate() {
+            node = self
+                .child(lines, node, segment)
+                .ok_or_else(|| EditError::NotFound(path_string(&path[..=depth])))?
+                .value;
+        }
+        Ok(node)
+    }
+
+    fn child(&self, lines: &[Line], node: Node, segment: &Segment) -> Option<Entry> {
+        let Node::Block { start, col, end } = node else {
+            return None;
+        };
+        let is_sequence = is_dash(self.content(&lines[start], col));
+        match segment {
+            Segment::Key(key) if !is_sequence => self
+                .entries(lines, start, col, end)
+                .into_iter()
+                .find(|entry| self.entry_key(lines, entry) == *key),
+            Segment::Index(index) if is_sequence => {
+                self.entries(lines, start, col, end).into_iter().nth(*index)
+            }
+            _ => None,
+        }
+    }
+
+    fn entry_key(&self, lines: &[Line], entry: &Entry) -> String {
+        let line = &lines[entry.line];
+        let raw = self.text[line.start + entry.col..line.start + entry.after - 1].trim();
+        serde_yaml::from_str::<String>(raw).unwrap_or_else(|_| raw.to_string())
+    }
+
+    // Entries of a block mapping, or items of a block sequence, starting at (start, col).
+    fn entries(&self, lines: &[Line], start: usize, col: usize, end: usize) -> Vec<Entry> {
+        let is_sequence = is_dash(self.content(&lines[start], col));
+        let mut heads = vec![start];
+        for (idx, line) in lines.iter().enumerate().take(end).skip(start + 1) {
+            if line.is_content()
+                && line.indent == col
+                && is_dash(self.content(line, col)) == is_sequence
+            {
+                heads.push(idx);
+            }
+        }
+        let mut entries = Vec::new();
+        for (n, &line) in heads.iter().enumerate() {
+            let next = heads.get(n + 1).copied().unwrap_or(end);
+            let entry_end = trim_end(lines, next);
+            let head = self.content(&lines[line], col);
+            let after = if is_sequence {
+                col + 1
+            } else {
+                match key_end(head) {
+                    Some(colon) => col + colon + 1,
+                    None => continue,
+                }
+            };
+            let value = self.value_node(lines, line, after, entry_end, is_sequence);
+            entries.push(Entry {
+                line,
+                col,
+                after,
+                end: entry_end,
+                value,
+            });
+        }
+        entries
+    }
+
+    fn value_node(
+        &self,
+        lines: &[Line],
+        line: usize,
+        after: usize,
+        end: usize,
+        in_sequence: bool,
+    ) -> Node {
+        let head = &lines[line];
+        let rest = self.content(head, after.min(head.end));
+        let offset = rest.len() - rest.trim_start().len();
+        let value_col = after.min(head.end) + offset;
+        let rest = rest.trim_start();
+        if !rest.is_empty() {
+            let nested_block = (in_sequence && key_end(rest).is_some()) || is_dash(rest);
+            if nested_block {
+                return Node::Block {
+                    start: line,
+                    col: value_col,
+                    end,
+                };
+            }
+            return Node::Inline {
+                line,
+                start: value_col,
+                stop: head.end,
+                end,
+            };
+        }
+        match (line + 1..end).find(|&idx| lines[idx].is_content()) {
+            Some(first) => Node::Block {
+                start: first,
+                col: lines[first].indent,
+                end,
+            },
+            None => Node::Inline {
+                line,
+                start: value_col,
+                stop: value_col,
+                end: line + 1,
+            },
+        }
+    }
+
+    fn set(&mut self, path: &[Segment], value: Value) -> Result<(), EditError> {
+        let lines = scan_lines(&self.text);
+        let Some((last, parent_path)) = path.split_last() else {
+            return Err(EditError::NotEditable(String::new()));
+        };
+        // Find the deepest existing parent, the rest of the path is created.
+        let mut node = self
+            .root(lines.as_slice())
+            .ok_or_else(|| EditError::NotFound(path_string(path)))?;
+        let mut owner: Option<Entry> = None;
+        for (depth, segment) in parent_path.iter().enumerate() {
+            match self.child(&lines, node, segment) {
+                Some(entry) => {
+                    node = entry.value;
+                    owner = Some(entry);
+                }
+                None => return self.insert_missing(&lines, node, owner, path, depth, value),
+            }
+        }
+        match self.child(&lines, node, last) {
+            Some(entry) => self.replace(&lines, &entry, value, path),
+            None => self.insert_missing(&lines, node, owner, path, parent_path.len(), value),
+        }
+    }
+
+    fn insert_missing(
+        &mut self,
+        lines: &[Line],
+        node: Node,
+        owner: Option<Entry>,
+        path: &[Segment],
+        depth: usize,
+        value: Value,
+    ) -> Result<(), EditError> {
+        // The keys from `depth` on do not exist yet.
+        let rest = &path[depth..];
+        let mut value = value;
+        for segment in rest[1..].iter().rev() {
+            let Segment::Key(key) = segment else {
+                return Err(EditError::NotFound(path_string(path)));
+            };
+            let mut map = Mapping::new();
+            map.insert(key.as_str().into(), value);
+            value = Value::Mapping(map);
+        }
+        let Segment::Key(key) = &rest[0] else {
+            return Err(EditError::NotFound(path_string(path)));
+        };
+        match node {
+            Node::Block { start, col, end } if !is_dash(self.content(&lines[start], col)) => {
+                let at = lines[end - 1].start + lines[end - 1].len;
+                let text = render_entry(key, &value, col, self.indent_unit(lines));
+                self.text.insert_str(at, &format!("\n{}", text));
+                Ok(())
+            }
+            Node::Inline { start, stop, .. } if start == stop => {
+                // An empty value, such as `filters:` without any filters.
+                let owner = owner.ok_or_else(|| EditError::NotFound(path_string(path)))?;
+                let mut map = Mapping::new();
+                map.insert(key.as_str().into(), value);
+                self.replace(lines, &owner, Value::Mapping(map), &path[..depth])
+            }
+            _ => Err(EditError::NotEditable(path_string(path))),
+        }
+    }
+
+    fn replace(
+        &mut self,
+        lines: &[Line],
+        entry: &Entry,
+        value: Value,
+        path: &[Segment],
+    ) -> Result<(), EditError> {
+        let unit = self.indent_unit(lines);
+        let head = &lines[entry.line];
+        let is_compound = is_nested(&value);
+        match entry.value {
+            Node::Inline {
+                line,
+                start,
+                stop,
+                end,
+            } => {
+                let old = self.content(&lines[line], start).to_string();
+                let old = &old[..stop - start];
+                let from = head.start + start;
+                let to = if end - 1 == line {
+                    head.start + stop
+                } else {
+                    lines[end - 1].start + lines[end - 1].end
+                };
+                if !is_compound || old.starts_with('[') || old.starts_with('{') {
+                    let text = if is_compound {
+                        render_flow(&value)
+                    } else {
+                        render_scalar(&value, old)
+                    };
+                    let text = if start == entry.after {
+                        format!(" {}", text)
+                    } else {
+                        text
+                    };
+                    self.text.replace_range(from..to, &text);
+                } else {
+                    // Keep a trailing comment on the key line, put the block below it.
+                    let comment = self.line_text(head)[stop..].to_string();
+                    let block = render_block(&value, entry.col + unit, unit);
+                    let from = head.start + entry.after;
+                    let to = lines[end - 1].start + lines[end - 1].len;
+                    self.text
+                        .replace_range(from..to, &format!("{}\n{}", comment, block));
+                }
+                Ok(())
+            }
+            Node::Block { start, col, end } => {
+                let to = lines[end - 1].start + lines[end - 1].end;
+                if is_compound {
+                    let block = render_block(&value, col, unit);
+                    let from = lines[start].start + col;
+                    self.text.replace_range(from..to, &block[col..]);
+                } else if start == entry.line {
+                    return Err(EditError::NotEditable(path_string(path)));
+                } else {
+                    let from = head.start + entry.after;
+                    let text = format!(" {}", render_scalar(&value, ""));
+                    let head_end = head.start + head.len;
+                    // Keep the comment of the key line.
+                    let comment = self.text[head.start + head.end..head_end].to_string();
+                    self.text
+                        .replace_range(from..to, &format!("{}{}", text, comment));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn push(&mut self, path: &[Segment], value: Value) -> Result<(), EditError> {
+        let lines = scan_lines(&self.text);
+        if let Ok(Node::Block { start, col, end }) = self.find(&lines, path) {
+            if is_dash(self.content(&lines[start], col)) {
+                let item = render_block(&value, col + 2, self.indent_unit(&lines));
+                let at = lines[end - 1].start + lines[end - 1].len;
+                let text = format!("\n{}- {}", " ".repeat(col), &item[col + 2..]);
+                self.text.insert_str(at, &text);
+                return Ok(());
+            }
+            return Err(EditError::NotEditable(path_string(path)));
+        }
+        // Missing, empty or flow sequence: rewrite it as a whole.
+        let mut items = match self.value_at(path) {
+            Some(Value::Sequence(items)) => items,
+            Some(Value::Null) | None => Vec::new(),
+            Some(_) => return Err(EditError::NotEditable(path_string(path))),
+        };
+        items.push(value);
+        self.set(path, Value::Sequence(items))
+    }
+}
+
+// Serialize a value for writing, leaving out unset optional fields.
+fn to_value<T: Serialize>(value: &T) -> Result<Value, EditError> {
+    fn strip_nulls(value: &mut Value) {
+        match value {
+            Value::Mapping(map) => {
+                map.retain(|_, v| !v.is_null());
+                map.values_mut().for_each(strip_nulls);
+            }
+            Value::Sequence(items) => items.iter_mut().for_each(strip_nulls),
+            _ => {}
+        }
+    }
+    let mut value = serde_yaml::to_value(value).map_err(|e| EditError::Invalid(e.to_string()))?;
+    strip_nulls(&mut value);
+    Ok(value)
+}
+
+// Index of the line after the last content line before `end`.
+fn trim_end(lines: &[Line], end: usize) -> usize {
+    let mut end = end;
+    while end > 0 && !lines[end - 1].is_content() {
+        end -= 1;
+    }
+    end
+}
+
+fn to_yaml(value: &Value) -> String {
+    serde_yaml::to_string(value).unwrap_or_default()
+}
+
+fn is_nested(value: &Value) -> bool {
+    matches!(value, Value::Mapping(m) if !m.is_empty())
+        || matches!(value, Value::Sequence(s) if !s.is_empty())
+}
+
+// Write a value in block style, with every line starting at `indent`.
+// Nested levels are indented by `unit`, the indent step of the document.
+fn render_block(value: &Value, indent: usize, unit: usize) -> String {
+    let pad = " ".repeat(indent);
+    let lines: Vec<String> = match value {
+        Value::Mapping(map) if !map.is_empty() => map
+            .iter()
+            .map(|(key, value)| {
+                let key = to_yaml(key);
+                let key = key.trim_end();
+                if is_nested(value) {
+                    format!(
+                        "{}{}:\n{}",
+                        pad,
+                        key,
+                        render_block(value, indent + unit, unit)
+                    )
+                } else {
+                    format!("{}{}: {}", pad, key, render_scalar(value, ""))
+                }
+            })
+            .collect(),
+        Value::Sequence(items) if !items.is_empty() => items
+            .iter()
+            .map(|item| {
+                if is_nested(item) {
+                    // The first line of the item goes on the line of the dash.
+                    let block = render_block(item, indent + 2, unit);
+                    format!("{}- {}", pad, &block[indent + 2..])
+                } else {
+                    format!("{}- {}", pad, render_scalar(item, ""))
+                }
+            })
+            .collect(),
+        _ => vec![format!("{}{}", pad, render_scalar(value, ""))],
+    };
+    lines.join("\n")
+}
+
+fn render_entry(key: &str, value: &Value, indent: usize, unit: usize) -> String {
+    let mut map = Mapping::new();
+    map.insert(key.into(), value.clone());
+    render_block(&Value::Mapping(map), indent, unit)
+}
+
+// Write a scalar, keeping the quoting style of the value it replaces.
+fn render_scalar(value: &Value, old: &str) -> String {
+    match value {
+        Value::String(text) if old.starts_with('"') => {
+            serde_json::to_string(text).unwrap_or_default()
+        }
+        Value::String(text) if old.starts_with('\'') => format!("'{}'", text.replace('\'', "''")),
+        // A block scalar would need the indent of its key, use an escaped string instead.
+        Value::String(text) if text.contains('\n') => {
+            serde_json::to_string(text).unwrap_or_default()
+        }
+        _ => to_yaml(value).trim_end().to_string(),
+    }
+}
+
+/// Line and column, both 1-based, of the deepest entry along `path` found in `text`.
+pub(crate) fn locate(text: &str, path: &[Segment]) -> Option<(usize, usize)> {
+    let document = ConfigDocument {
+        text: text.to_string(),
+    };
+    let lines = scan_lines(text);
+    let mut node = document.root(&lines)?;
+    let mut found = None;
+    for segment in path {
+        let Some(entry) = document.child(&lines, node, segment) else {
+            break;
+        };
+        found = Some((entry.line + 1, entry.col + 1));
+        node = entry.value;
+    }
+    found
+}
+
+// Write a value on a single line, in flow style.
+pub(crate) fn render_flow(value: &Value) -> String {
+    match value {
+        Value::Sequence(items) => format!(
+            "[{}]",
+            items.iter().map(render_flow).collect::<Vec<_>>().join(", ")
+        ),
+        Value::Mapping(map) => format!(
+            "{{{}}}",
+            map.iter()
+                .map(|(k, v)| format!("{}: {}", render_flow(k), render_flow(v)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Value::String(text)
+            if text.chars().any(|c| ",[]{}:#'\"".contains(c)) || text.trim() != text =>
+        {
+            serde_json::to_string(text).unwrap_or_default()
+        }
+        _ => render_scalar(value, ""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"---
+# Living room system
+devices:
+  samplerate: 48000 # fixed by the DAC
+  chunksize: 1024
+  capture:
+    type: Alsa
+    channels: 2
+    device: "hw:Loopback,0,1"
+  playback:
+    type: Alsa
+    channels: 2
+    device: 'hw:DAC'
+
+filters:
+  # Tuned by ear, do not touch
+  zbass:
+    type: Biquad
+    parameters:
+      type: Lowshelf
+      freq: 80
+      q: 0.7
+      gain: 3   # was 4
+  headroom:
+    type: Gain
+    parameters:
+      gain: -6
+
+pipeline:
+  - type: Filter
+    channels: [0, 1]
+    names:
+      - headroom
+      - zbass   # bass last
+"#;
+
+    #[test]
+    fn test_scalar_edits_keep_comments() {
+        let mut doc = ConfigDocument::new(CONFIG).unwrap();
+        doc.set_filter_gain("zbass", -2.5).unwrap();
+        doc.set_value(&["devices".into(), "samplerate".into()], &44100)
+            .unwrap();
+        doc.set_value(
+            &["devices".into(), "playback".into(), "device".into()],
+            &"hw:USB",
+        )
+        .unwrap();
+        let expected = CONFIG
+            .replace("gain: 3   # was 4", "gain: -2.5   # was 4")
+            .replace("48000 # fixed", "44100 # fixed")
+            .replace("'hw:DAC'", "'hw:USB'");
+        assert_eq!(doc.text(), expected);
+        let config = doc.configuration().unwrap();
+        assert_eq!(config.devices.samplerate, 44100);
+
+        assert_eq!(
+            doc.set_filter_gain("missing", 1.0),
+            Err(EditError::NotFound(
+                "filters.missing.parameters.gain".to_string()
+            ))
+        );
+        // An edit that breaks the config is rolled back.
+        assert!(doc
+            .set_value(&["devices".into(), "samplerate".into()], &"fast")
+            .is_err());
+        assert_eq!(doc.text(), expected);
+
+        // An apostrophe in a plain scalar does not hide the comment after it.
+        let mut doc =
+            ConfigDocument::new(CONFIG.replace("# Living room system", "title: it's mine # note"))
+                .unwrap();
+        doc.set_value(&["title".into()], &"new").unwrap();
+        assert!(doc.text().starts_with("---\ntitle: new # note\n"));
+
+        // Values inside flow mappings can not be edited in place.
+        let mut doc = ConfigDocument::new(
+            CONFIG.replace("parameters:\n      gain: -6", "parameters: {gain: -6}"),
+        )
+        .unwrap();
+        assert_eq!(
+            doc.set_filter_gain("headroom", -3.0),
+            Err(EditError::NotEditable(
+                "filters.headroom.parameters.gain".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_structural_edits() {
+        let mut doc = ConfigDocument::new(CONFIG).unwrap();
+        doc.add_filter("lp", &Filter::lowpass(18000.0, 0.707))
+            .unwrap();
+        doc.add_pipeline_step(&PipelineStep::Filter(PipelineStepFilter {
+            channels: None,
+            names: vec!["lp".to_string()],
+            description: None,
+            bypassed: None,
+        }))
+        .unwrap();
+        doc.set_step_bypassed(0, true).unwrap();
+        doc.set_value(&["pipeline".into(), 0.into(), "channels".into()], &vec![1])
+            .unwrap();
+        assert_eq!(
+            doc.add_filter("lp", &Filter::gain(0.0)),
+            Err(EditError::AlreadyExists("filters.lp".to_string()))
+        );
+
+        let text = doc.text();
+        assert!(text.starts_with("---\n# Living room system\n"));
+        assert!(text.contains("  # Tuned by ear, do not touch\n  zbass:\n"));
+        assert!(text.contains("      - zbass   # bass last\n"));
+        assert!(text.contains("    channels: [1]\n"));
+
+        let config = doc.configuration().unwrap();
+        let names: Vec<&String> = config.filters.as_ref().unwrap().keys().collect();
+        assert_eq!(names, vec!["zbass", "headroom", "lp"]);
+        let pipeline = config.pipeline.as_ref().unwrap();
+        assert_eq!(pipeline.len(), 2);
+        assert!(pipeline[0].is_bypassed());
+
+        doc.remove_pipeline_step(0).unwrap();
+        let config = doc.configuration().unwrap();
+        assert_eq!(config.pipeline.as_ref().unwrap().len(), 1);
+
+        // A config without a pipeline gets one.
+        let mut doc =
+            ConfigDocument::new(CONFIG.split("\npipeline:").next().unwrap().to_string() + "\n")
+                .unwrap();
+        doc.add_mixer(
+            "m",
+            &Mixer {
+                description: None,
+                channels: MixerChannels { r#in: 2, out: 2 },
+                mapping: vec![],
+                labels: None,
+            },
+        )
+        .unwrap();
+        doc.add_pipeline_step(&PipelineStep::Mixer(PipelineStepMixer {
+            name: "m".to_string(),
+            description: None,
+            bypassed: None,
+        }))
+        .unwrap();
+        assert_eq!(doc.configuration().unwrap().pipeline.unwrap().len(), 1);
+
+        // Added blocks follow the indent step of the document.
+        let mut doc = ConfigDocument::new(
+            r#"---
+devices:
+    samplerate: 48000
+    chunksize: 1024
+    capture:
+        type: Stdin
+        channels: 2
+        format: S16_LE
+    playback:
+        type: Stdout
+        channels: 2
+        format: S16_LE
+filters:
+    vol:
+        type: Gain
+        parameters:
+            gain: -6
+pipeline:
+    - type: Filter
+      names: [vol]
+"#,
+        )
+        .unwrap();
+        doc.add_filter("lp", &Filter::lowpass(18000.0, 0.707))
+            .unwrap();
+        doc.add_pipeline_step(&PipelineStep::Filter(PipelineStepFilter {
+            channels: Some(vec![0]),
+            names: vec!["lp".to_string()],
+            description: None,
+            bypassed: None,
+        }))
+        .unwrap();
+        let text = doc.text();
+        assert!(text.contains(
+            "\n    lp:\n        type: Biquad\n        parameters:\n            type: Lowpass\n"
+        ));
+        assert!(text.ends_with(
+            "\n    - type: Filter\n      channels:\n          - 0\n      names:\n          - lp\n"
+        ));
+    }
+}
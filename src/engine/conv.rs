@@ -0,0 +1,154 @@
+use std::fs;
+
+use crate::types::*;
+
+use super::EngineError;
+
+fn io_error(filename: &str, error: impl std::fmt::Display) -> EngineError {
+    EngineError::Io(format!("{}: {}", filename, error))
+}
+
+fn bytes_per_sample(format: FileSampleFormat) -> usize {
+    match format {
+        FileSampleFormat::TEXT => 0,
+        FileSampleFormat::S16_LE => 2,
+        FileSampleFormat::S24_3_LE => 3,
+        FileSampleFormat::S24_4_RJ_LE
+        | FileSampleFormat::S24_4_LJ_LE
+        | FileSampleFormat::S32_LE
+        | FileSampleFormat::F32_LE => 4,
+        FileSampleFormat::F64_LE => 8,
+    }
+}
+
+fn decode_sample(format: FileSampleFormat, bytes: &[u8]) -> f64 {
+    match format {
+        FileSampleFormat::S16_LE => i16::from_le_bytes([bytes[0], bytes[1]]) as f64 / 32768.0,
+        FileSampleFormat::S24_3_LE | FileSampleFormat::S24_4_RJ_LE => {
+            let value = i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8;
+            value as f64 / 8388608.0
+        }
+        FileSampleFormat::S24_4_LJ_LE => {
+            let value = i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) >> 8;
+            value as f64 / 8388608.0
+        }
+        FileSampleFormat::S32_LE => {
+            i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64 / 2147483648.0
+        }
+        FileSampleFormat::F32_LE => {
+            f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64
+        }
+        FileSampleFormat::F64_LE => f64::from_le_bytes(bytes[..8].try_into().unwrap()),
+        FileSampleFormat::TEXT => unreachable!(),
+    }
+}
+
+fn read_raw(parameters: &ConvParametersRaw) -> Result<Vec<f64>, EngineError> {
+    let filename = &parameters.filename;
+    let format = parameters.format.unwrap_or(FileSampleFormat::TEXT);
+    let skip = parameters.skip_bytes_lines.unwrap_or(0);
+    let read = parameters.read_bytes_lines.unwrap_or(0);
+    if format == FileSampleFormat::TEXT {
+        let text = fs::read_to_string(filename).map_err(|e| io_error(filename, e))?;
+        let lines = text.lines().skip(skip);
+        let lines: Vec<&str> = if read > 0 {
+            lines.take(read).collect()
+        } else {
+            lines.collect()
+        };
+        lines
+            .into_iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                line.trim()
+                    .parse::<f64>()
+                    .map_err(|e| io_error(filename, e))
+            })
+            .collect()
+    } else {
+        let data = fs::read(filename).map_err(|e| io_error(filename, e))?;
+        let data = data.get(skip..).unwrap_or(&[]);
+        let data = if read > 0 && read < data.len() {
+            &data[..read]
+        } else {
+            data
+        };
+        Ok(data
+            .chunks_exact(bytes_per_sample(format))
+            .map(|bytes| decode_sample(format, bytes))
+            .collect())
+    }
+}
+
+fn read_wav(parameters: &ConvParametersWav) -> Result<Vec<f64>, EngineError> {
+    let filename = &parameters.filename;
+    let data = fs::read(filename).map_err(|e| io_error(filename, e))?;
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return Err(io_error(filename, "not a RIFF WAVE file"));
+    }
+    let mut format = None;
+    let mut samples = None;
+    let mut pos = 12;
+    while pos + 8 <= data.len() {
+        let id = &data[pos..pos + 4];
+        let size = u32::from_le_bytes(data[pos + 4..pos + 8].try_into().unwrap()) as usize;
+        let body = &data[pos + 8..(pos + 8 + size).min(data.len())];
+        if id == b"fmt " && body.len() >= 16 {
+            let tag = u16::from_le_bytes([body[0], body[1]]);
+            let channels = u16::from_le_bytes([body[2], body[3]]) as usize;
+            let bits = u16::from_le_bytes([body[14], body[15]]);
+            format = Some((tag, channels, bits));
+        } else if id == b"data" {
+            samples = Some(body);
+        }
+        // Chunks are padded to an even number of bytes.
+        pos += 8 + size + size % 2;
+    }
+    let (Some((tag, channels, bits)), Some(samples)) = (format, samples) else {
+        return Err(io_error(filename, "missing fmt or data chunk"));
+    };
+    let sample_format = match (tag, bits) {
+        (1, 16) | (0xFFFE, 16) => FileSampleFormat::S16_LE,
+        (1, 24) | (0xFFFE, 24) => FileSampleFormat::S24_3_LE,
+        (1, 32) | (0xFFFE, 32) => FileSampleFormat::S32_LE,
+        (3, 32) => FileSampleFormat::F32_LE,
+        (3, 64) => FileSampleFormat::F64_LE,
+        _ => {
+            return Err(io_error(
+                filename,
+                format!("unsupported sample format {} with {} bits", tag, bits),
+            ))
+        }
+    };
+    let channel = parameters.channel.unwrap_or(0);
+    if channel >= channels {
+        return Err(io_error(
+            filename,
+            format!("channel {} requested but file has {}", channel, channels),
+        ));
+    }
+    let width = bytes_per_sample(sample_format);
+    Ok(samples
+        .chunks_exact(width * channels)
+        .map(|frame| decode_sample(sample_format, &frame[channel * width..]))
+        .collect())
+}
+
+pub(crate) fn coefficients(parameters: &ConvParameters) -> Result<Vec<f64>, EngineError> {
+    let coeffs = match parameters {
+        ConvParameters::Values { values } => values.clone(),
+        ConvParameters::Dummy { length } => {
+            let mut coeffs = vec![0.0; (*length).max(1)];
+            coeffs[0] = 1.0;
+            coeffs
+        }
+        ConvParameters::Raw(raw) => read_raw(raw)?,
+        ConvParameters::Wav(wav) => read_wav(wav)?,
+    };
+    if coeffs.is_empty() {
+        return Err(EngineError::Unsupported(
+            "convolution filter without coefficients".to_string(),
+        ));
+    }
+    Ok(coeffs)
+}
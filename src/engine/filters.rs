@@ -0,0 +1,311 @@
+use crate::dsp::BiquadCoefficients;
+use crate::types::*;
+
+use super::conv;
+use super::EngineError;
+
+pub(crate) trait ChannelFilter {
+    fn process(&mut self, waveform: &mut [f64]);
+}
+
+// --- Biquad, transposed direct form 2 ---
+
+pub(crate) struct Biquad {
+    coeffs: BiquadCoefficients,
+    s1: f64,
+    s2: f64,
+}
+
+impl Biquad {
+    pub(crate) fn new(coeffs: BiquadCoefficients) -> Self {
+        Biquad {
+            coeffs,
+            s1: 0.0,
+            s2: 0.0,
+        }
+    }
+}
+
+impl ChannelFilter for Biquad {
+    fn process(&mut self, waveform: &mut [f64]) {
+        let c = self.coeffs;
+        for x in waveform.iter_mut() {
+            let y = c.b0 * *x + self.s1;
+            self.s1 = c.b1 * *x - c.a1 * y + self.s2;
+            self.s2 = c.b2 * *x - c.a2 * y;
+            *x = y;
+        }
+    }
+}
+
+// --- Gain ---
+
+struct Gain {
+    gain: f64,
+}
+
+impl ChannelFilter for Gain {
+    fn process(&mut self, waveform: &mut [f64]) {
+        for x in waveform.iter_mut() {
+            *x *= self.gain;
+        }
+    }
+}
+
+// --- Pass-through, for filters that only act on runtime controls ---
+
+struct PassThrough;
+
+impl ChannelFilter for PassThrough {
+    fn process(&mut self, _waveform: &mut [f64]) {}
+}
+
+// --- Delay ---
+
+pub(crate) struct Delay {
+    buffer: Vec<f64>,
+    index: usize,
+    // First order allpass approximating the fractional part of the delay.
+    fraction: Option<(f64, f64, f64)>,
+}
+
+impl Delay {
+    pub(crate) fn new(samples: f64, subsample: bool) -> Self {
+        let samples = samples.max(0.0);
+        let (integer, fraction) = if subsample {
+            let integer = samples.floor();
+            let frac = samples - integer;
+            if frac > 1e-9 {
+                (
+                    integer as usize,
+                    Some(((1.0 - frac) / (1.0 + frac), 0.0, 0.0)),
+                )
+            } else {
+                (integer as usize, None)
+            }
+        } else {
+            (samples.round() as usize, None)
+        };
+        Delay {
+            buffer: vec![0.0; integer],
+            index: 0,
+            fraction,
+        }
+    }
+}
+
+impl ChannelFilter for Delay {
+    fn process(&mut self, waveform: &mut [f64]) {
+        for x in waveform.iter_mut() {
+            if !self.buffer.is_empty() {
+                let delayed = self.buffer[self.index];
+                self.buffer[self.index] = *x;
+                self.index = (self.index + 1) % self.buffer.len();
+                *x = delayed;
+            }
+            if let Some((coeff, x_prev, y_prev)) = self.fraction.as_mut() {
+                let y = *coeff * *x + *x_prev - *coeff * *y_prev;
+                *x_prev = *x;
+                *y_prev = y;
+                *x = y;
+            }
+        }
+    }
+}
+
+// --- FIR, direct convolution ---
+
+struct Fir {
+    coeffs: Vec<f64>,
+    history: Vec<f64>,
+}
+
+impl ChannelFilter for Fir {
+    fn process(&mut self, waveform: &mut [f64]) {
+        for x in waveform.iter_mut() {
+            self.history.rotate_right(1);
+            self.history[0] = *x;
+            *x = self
+                .coeffs
+                .iter()
+                .zip(&self.history)
+                .map(|(c, h)| c * h)
+                .sum();
+        }
+    }
+}
+
+// --- DiffEq ---
+
+struct DiffEq {
+    a: Vec<f64>,
+    b: Vec<f64>,
+    x_hist: Vec<f64>,
+    y_hist: Vec<f64>,
+}
+
+impl ChannelFilter for DiffEq {
+    fn process(&mut self, waveform: &mut [f64]) {
+        for x in waveform.iter_mut() {
+            self.x_hist.rotate_right(1);
+            self.x_hist[0] = *x;
+            let mut y: f64 = self.b.iter().zip(&self.x_hist).map(|(b, x)| b * x).sum();
+            y -= self.a[1..]
+                .iter()
+                .zip(&self.y_hist)
+                .map(|(a, y)| a * y)
+                .sum::<f64>();
+            y /= self.a[0];
+            if !self.y_hist.is_empty() {
+                self.y_hist.rotate_right(1);
+                self.y_hist[0] = y;
+            }
+            *x = y;
+        }
+    }
+}
+
+// --- Limiter ---
+
+pub(crate) fn soft_clip(value: f64, limit: f64) -> f64 {
+    let scaled = (value / limit).clamp(-1.5, 1.5);
+    (scaled - 4.0 / 27.0 * scaled.powi(3)) * limit
+}
+
+struct Limiter {
+    limit: f64,
+    soft: bool,
+}
+
+impl ChannelFilter for Limiter {
+    fn process(&mut self, waveform: &mut [f64]) {
+        for x in waveform.iter_mut() {
+            *x = if self.soft {
+                soft_clip(*x, self.limit)
+            } else {
+                x.clamp(-self.limit, self.limit)
+            };
+        }
+    }
+}
+
+// --- Dither ---
+
+// Noise shaping is not modelled, all types quantize with triangular dither.
+struct Dither {
+    scale: f64,
+    amplitude: f64,
+    state: u64,
+}
+
+impl Dither {
+    fn random(&mut self) -> f64 {
+        // xorshift64, deterministic so renders are reproducible.
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl ChannelFilter for Dither {
+    fn process(&mut self, waveform: &mut [f64]) {
+        for x in waveform.iter_mut() {
+            let noise = if self.amplitude > 0.0 {
+                self.amplitude * (self.random() - self.random())
+            } else {
+                0.0
+            };
+            *x = (*x * self.scale + noise).round() / self.scale;
+        }
+    }
+}
+
+fn dither(parameters: &DitherParameters) -> Dither {
+    let (bits, amplitude) = match *parameters {
+        DitherParameters::None { bits } => (bits, 0.0),
+        DitherParameters::Flat { bits, amplitude } => (bits, amplitude),
+        DitherParameters::Highpass { bits }
+        | DitherParameters::Fweighted441 { bits }
+        | DitherParameters::FweightedLong441 { bits }
+        | DitherParameters::FweightedShort441 { bits }
+        | DitherParameters::Gesemann441 { bits }
+        | DitherParameters::Gesemann48 { bits }
+        | DitherParameters::Lipshitz441 { bits }
+        | DitherParameters::LipshitzLong441 { bits }
+        | DitherParameters::Shibata441 { bits }
+        | DitherParameters::ShibataHigh441 { bits }
+        | DitherParameters::ShibataLow441 { bits }
+        | DitherParameters::Shibata48 { bits }
+        | DitherParameters::ShibataHigh48 { bits }
+        | DitherParameters::ShibataLow48 { bits }
+        | DitherParameters::Shibata882 { bits }
+        | DitherParameters::ShibataLow882 { bits }
+        | DitherParameters::Shibata96 { bits }
+        | DitherParameters::ShibataLow96 { bits }
+        | DitherParameters::Shibata192 { bits }
+        | DitherParameters::ShibataLow192 { bits } => (bits, 1.0),
+    };
+    Dither {
+        scale: 2.0_f64.powi(bits as i32 - 1),
+        amplitude,
+        state: 0x2545_f491_4f6c_dd1d,
+    }
+}
+
+pub(crate) fn build(
+    filter: &Filter,
+    samplerate: usize,
+) -> Result<Vec<Box<dyn ChannelFilter>>, EngineError> {
+    let filters: Vec<Box<dyn ChannelFilter>> = match filter {
+        Filter::Biquad { parameters, .. } => vec![Box::new(Biquad::new(
+            BiquadCoefficients::from_config(samplerate, parameters),
+        ))],
+        Filter::BiquadCombo { parameters, .. } => parameters
+            .expand()
+            .map_err(EngineError::InvalidCombo)?
+            .iter()
+            .map(|biquad| {
+                Box::new(Biquad::new(BiquadCoefficients::from_config(
+                    samplerate, biquad,
+                ))) as Box<dyn ChannelFilter>
+            })
+            .collect(),
+        Filter::Gain { parameters, .. } => vec![Box::new(Gain {
+            gain: parameters.linear_gain(),
+        })],
+        Filter::Delay { parameters, .. } => vec![Box::new(Delay::new(
+            parameters
+                .unit
+                .unwrap_or(TimeUnit::Milliseconds)
+                .to_samples(parameters.delay, samplerate),
+            parameters.subsample.unwrap_or(false),
+        ))],
+        Filter::Conv { parameters, .. } => {
+            let coeffs = conv::coefficients(parameters)?;
+            vec![Box::new(Fir {
+                history: vec![0.0; coeffs.len()],
+                coeffs,
+            })]
+        }
+        Filter::DiffEq { parameters, .. } => {
+            let (a, b) = parameters
+                .coefficients()
+                .map_err(EngineError::Unsupported)?;
+            vec![Box::new(DiffEq {
+                x_hist: vec![0.0; b.len()],
+                y_hist: vec![0.0; a.len() - 1],
+                a,
+                b,
+            })]
+        }
+        Filter::Limiter { parameters, .. } => vec![Box::new(Limiter {
+            limit: 10.0_f64.powf(parameters.clip_limit / 20.0),
+            soft: parameters.soft_clip.unwrap_or(false),
+        })],
+        Filter::Dither { parameters, .. } => vec![Box::new(dither(parameters))],
+        // Volume and loudness follow the faders, which stay at 0 dB in offline rendering.
+        Filter::Volume { .. } | Filter::Loudness { .. } => vec![Box::new(PassThrough)],
+    };
+    Ok(filters)
+}
@@ -0,0 +1,353 @@
+mod conv;
+mod filters;
+mod processors;
+
+use std::fmt;
+
+use crate::dsp::ComboError;
+use crate::types::*;
+use crate::validation::ValidationError;
+
+use filters::ChannelFilter;
+use processors::ChunkProcessor;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EngineError {
+    InvalidConfig(Vec<ValidationError>),
+    InvalidCombo(ComboError),
+    UnknownChannelCount,
+    Unsupported(String),
+    Io(String),
+    BufferLength { len: usize, channels: usize },
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineError::InvalidConfig(errors) => {
+                write!(f, "invalid config")?;
+                for error in errors {
+                    write!(f, "\n  {}", error)?;
+                }
+                Ok(())
+            }
+            EngineError::InvalidCombo(error) => write!(f, "invalid combo filter: {}", error),
+            EngineError::UnknownChannelCount => {
+                write!(f, "the number of capture channels is not known")
+            }
+            EngineError::Unsupported(reason) => write!(f, "unsupported: {}", reason),
+            EngineError::Io(reason) => write!(f, "could not read file: {}", reason),
+            EngineError::BufferLength { len, channels } => write!(
+                f,
+                "buffer of {} samples is not a whole number of {} channel frames",
+                len, channels
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+struct MixerStep {
+    out: usize,
+    // (dest, source, gain)
+    gains: Vec<(usize, usize, f64)>,
+}
+
+enum Step {
+    Filter {
+        channels: Vec<usize>,
+        filters: Vec<Vec<Box<dyn ChannelFilter>>>,
+    },
+    Mixer(MixerStep),
+    Processor(Box<dyn ChunkProcessor>),
+}
+
+/// Offline reference implementation of the CamillaDSP pipeline.
+/// Audio is processed in chunks of `devices.chunksize` frames and the filter
+/// state is kept between calls to `process`.
+pub struct Engine {
+    chunksize: usize,
+    capture_channels: usize,
+    playback_channels: usize,
+    steps: Vec<Step>,
+}
+
+impl Engine {
+    pub fn new(config: &Configuration) -> Result<Self, EngineError> {
+        config.validate().map_err(EngineError::InvalidConfig)?;
+        let samplerate = config.devices.samplerate;
+        let capture_channels = config
+            .devices
+            .capture
+            .channels()
+            .ok_or(EngineError::UnknownChannelCount)?;
+        let mut channels = capture_channels;
+        let mut steps = Vec::new();
+        for step in config.pipeline.iter().flatten() {
+            if step.is_bypassed() {
+                continue;
+            }
+            match step {
+                PipelineStep::Filter(step) => {
+                    let step_channels = match &step.channels {
+                        Some(list) => list.clone(),
+                        None => (0..channels).collect(),
+                    };
+                    let mut filters = Vec::new();
+                    for _ in &step_channels {
+                        let mut chain = Vec::new();
+                        for name in &step.names {
+                            // References were checked by validate.
+                            let filter = &config.filters.as_ref().unwrap()[name];
+                            chain.extend(filters::build(filter, samplerate)?);
+                        }
+                        filters.push(chain);
+                    }
+                    steps.push(Step::Filter {
+                        channels: step_channels,
+                        filters,
+                    });
+                }
+                PipelineStep::Mixer(step) => {
+                    let mixer = &config.mixers.as_ref().unwrap()[&step.name];
+                    let gains = mixer
+                        .mapping
+                        .iter()
+                        .filter(|mapping| !mapping.mute.unwrap_or(false))
+                        .flat_map(|mapping| {
+                            mapping
+                                .sources
+                                .iter()
+                                .map(|source| (mapping.dest, source.channel, source.linear_gain()))
+                        })
+                        .collect();
+                    channels = mixer.channels.out;
+                    steps.push(Step::Mixer(MixerStep {
+                        out: mixer.channels.out,
+                        gains,
+                    }));
+                }
+                PipelineStep::Processor(step) => {
+                    let processor = &config.processors.as_ref().unwrap()[&step.name];
+                    steps.push(Step::Processor(processors::build(processor, samplerate)));
+                }
+            }
+        }
+        Ok(Engine {
+            chunksize: config.devices.chunksize.max(1),
+            capture_channels,
+            playback_channels: config.devices.playback.channels(),
+            steps,
+        })
+    }
+
+    pub fn capture_channels(&self) -> usize {
+        self.capture_channels
+    }
+
+    pub fn playback_channels(&self) -> usize {
+        self.playback_channels
+    }
+
+    /// Process interleaved samples with one frame per capture channel,
+    /// returning interleaved samples with one frame per playback channel.
+    pub fn process(&mut self, input: &[f64]) -> Result<Vec<f64>, EngineError> {
+        let channels = self.capture_channels;
+        if channels == 0 || !input.len().is_multiple_of(channels) {
+            return Err(EngineError::BufferLength {
+                len: input.len(),
+                channels,
+            });
+        }
+        let mut output = Vec::with_capacity(input.len() / channels * self.playback_channels);
+        for chunk in input.chunks(self.chunksize * channels) {
+            let frames = chunk.len() / channels;
+            let waveforms: Vec<Vec<f64>> = (0..channels)
+                .map(|ch| (0..frames).map(|n| chunk[n * channels + ch]).collect())
+                .collect();
+            let waveforms = self.process_chunk(waveforms);
+            for n in 0..frames {
+                for waveform in &waveforms {
+                    output.push(waveform[n]);
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    fn process_chunk(&mut self, mut waveforms: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+        let frames = waveforms.first().map_or(0, |w| w.len());
+        for step in self.steps.iter_mut() {
+            match step {
+                Step::Filter { channels, filters } => {
+                    for (channel, chain) in channels.iter().zip(filters.iter_mut()) {
+                        for filter in chain.iter_mut() {
+                            filter.process(&mut waveforms[*channel]);
+                        }
+                    }
+                }
+                Step::Mixer(mixer) => {
+                    let mut mixed = vec![vec![0.0; frames]; mixer.out];
+                    for (dest, source, gain) in &mixer.gains {
+                        for (m, x) in mixed[*dest].iter_mut().zip(&waveforms[*source]) {
+                            *m += x * gain;
+                        }
+                    }
+                    waveforms = mixed;
+                }
+                Step::Processor(processor) => processor.process(&mut waveforms),
+            }
+        }
+        waveforms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(extra: &str) -> Configuration {
+        let yaml = format!(
+            r#"---
+devices:
+  samplerate: 48000
+  chunksize: 64
+  capture:
+    type: Stdin
+    channels: 2
+    format: S16_LE
+  playback:
+    type: Stdout
+    channels: 2
+    format: S16_LE
+{}"#,
+            extra
+        );
+        Configuration::from_yaml_string(&yaml).unwrap()
+    }
+
+    #[test]
+    fn test_mixer_gain_and_delay() {
+        let config = config(
+            r#"
+filters:
+  half:
+    type: Gain
+    parameters:
+      gain: 0.5
+      scale: linear
+  late:
+    type: Delay
+    parameters:
+      delay: 3
+      unit: samples
+mixers:
+  swap:
+    channels:
+      in: 2
+      out: 2
+    mapping:
+      - dest: 0
+        sources:
+          - channel: 1
+      - dest: 1
+        sources:
+          - channel: 0
+pipeline:
+  - type: Mixer
+    name: swap
+  - type: Filter
+    channels: [0]
+    names: [half]
+  - type: Filter
+    channels: [1]
+    names: [late]
+"#,
+        );
+        let mut engine = Engine::new(&config).unwrap();
+        // Impulse on the left input, a constant on the right.
+        let mut input = vec![0.0; 2 * 200];
+        input[2 * 62] = 1.0;
+        for n in 0..200 {
+            input[2 * n + 1] = 0.8;
+        }
+        let output = engine.process(&input).unwrap();
+        assert_eq!(output.len(), 400);
+        // Left output is the scaled right input.
+        assert!(output.iter().step_by(2).all(|x| (x - 0.4).abs() < 1e-12));
+        // Right output is the left impulse delayed by 3 samples,
+        // from the end of the first 64 sample chunk into the second.
+        let right: Vec<f64> = output.iter().skip(1).step_by(2).copied().collect();
+        assert_eq!(right[65], 1.0);
+        assert_eq!(right.iter().filter(|x| **x != 0.0).count(), 1);
+    }
+
+    #[test]
+    fn test_lowpass_and_limiter_keep_peak_below_0dbfs() {
+        let config = config(
+            r#"
+filters:
+  boost:
+    type: Gain
+    parameters:
+      gain: 12
+  lp:
+    type: BiquadCombo
+    parameters:
+      type: LinkwitzRileyLowpass
+      freq: 1000
+      order: 4
+  limit:
+    type: Limiter
+    parameters:
+      clip_limit: -1
+      soft_clip: true
+pipeline:
+  - type: Filter
+    names: [boost, lp, limit]
+"#,
+        );
+        let mut engine = Engine::new(&config).unwrap();
+        let input: Vec<f64> = (0..4800)
+            .flat_map(|n| {
+                let x = (2.0 * std::f64::consts::PI * 100.0 * n as f64 / 48000.0).sin();
+                [x, 0.5 * x]
+            })
+            .collect();
+        let output = engine.process(&input).unwrap();
+        let peak = output.iter().fold(0.0_f64, |p, x| p.max(x.abs()));
+        assert!(peak <= 10.0_f64.powf(-1.0 / 20.0) + 1e-12);
+        assert!(peak > 0.8);
+
+        assert_eq!(
+            engine.process(&[0.0; 3]),
+            Err(EngineError::BufferLength {
+                len: 3,
+                channels: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_diffeq_without_b_coefficients() {
+        let config = config(
+            r#"
+filters:
+  fir:
+    type: DiffEq
+    parameters:
+      b: []
+pipeline:
+  - type: Filter
+    names: [fir]
+"#,
+        );
+        assert_eq!(
+            Engine::new(&config).err(),
+            Some(EngineError::Unsupported(
+                "DiffEq needs at least one b coefficient".to_string()
+            ))
+        );
+    }
+}
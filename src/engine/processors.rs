@@ -0,0 +1,186 @@
+use crate::types::*;
+
+use super::filters::{soft_clip, ChannelFilter, Delay};
+
+pub(crate) trait ChunkProcessor {
+    fn process(&mut self, waveforms: &mut [Vec<f64>]);
+}
+
+// Attack/release smoothed level in dB, as used by the compressor and the noise gate.
+struct LoudnessEstimator {
+    attack: f64,
+    release: f64,
+    prev: f64,
+}
+
+impl LoudnessEstimator {
+    fn new(attack: f64, release: f64, samplerate: usize) -> Self {
+        let fs = samplerate as f64;
+        LoudnessEstimator {
+            attack: (-1.0 / (fs * attack)).exp(),
+            release: (-1.0 / (fs * release)).exp(),
+            prev: -100.0,
+        }
+    }
+
+    fn estimate(&mut self, waveforms: &[Vec<f64>], monitor: &[usize]) -> Vec<f64> {
+        let frames = waveforms.first().map_or(0, |w| w.len());
+        (0..frames)
+            .map(|n| {
+                let sum: f64 = monitor.iter().map(|ch| waveforms[*ch][n]).sum();
+                let level = 20.0 * (sum.abs() + 1.0e-9).log10();
+                let coeff = if level >= self.prev {
+                    self.attack
+                } else {
+                    self.release
+                };
+                self.prev = coeff * self.prev + (1.0 - coeff) * level;
+                self.prev
+            })
+            .collect()
+    }
+}
+
+fn channel_list(list: &Option<Vec<usize>>, channels: usize) -> Vec<usize> {
+    list.clone().unwrap_or_else(|| (0..channels).collect())
+}
+
+// --- Compressor ---
+
+struct Compressor {
+    estimator: LoudnessEstimator,
+    monitor: Vec<usize>,
+    process: Vec<usize>,
+    threshold: f64,
+    factor: f64,
+    makeup_gain: f64,
+    clip: Option<(f64, bool)>,
+}
+
+impl ChunkProcessor for Compressor {
+    fn process(&mut self, waveforms: &mut [Vec<f64>]) {
+        let levels = self.estimator.estimate(waveforms, &self.monitor);
+        let gains: Vec<f64> = levels
+            .iter()
+            .map(|level| {
+                let mut gain = 0.0;
+                if *level > self.threshold {
+                    gain = -(level - self.threshold) * (self.factor - 1.0) / self.factor;
+                }
+                10.0_f64.powf((gain + self.makeup_gain) / 20.0)
+            })
+            .collect();
+        for ch in &self.process {
+            for (x, gain) in waveforms[*ch].iter_mut().zip(&gains) {
+                *x *= gain;
+                if let Some((limit, soft)) = self.clip {
+                    *x = if soft {
+                        soft_clip(*x, limit)
+                    } else {
+                        x.clamp(-limit, limit)
+                    };
+                }
+            }
+        }
+    }
+}
+
+// --- NoiseGate ---
+
+struct NoiseGate {
+    estimator: LoudnessEstimator,
+    monitor: Vec<usize>,
+    process: Vec<usize>,
+    threshold: f64,
+    attenuation: f64,
+}
+
+impl ChunkProcessor for NoiseGate {
+    fn process(&mut self, waveforms: &mut [Vec<f64>]) {
+        let levels = self.estimator.estimate(waveforms, &self.monitor);
+        for ch in &self.process {
+            for (x, level) in waveforms[*ch].iter_mut().zip(&levels) {
+                if *level < self.threshold {
+                    *x *= self.attenuation;
+                }
+            }
+        }
+    }
+}
+
+// --- RACE ---
+
+struct Race {
+    channel_a: usize,
+    channel_b: usize,
+    gain: f64,
+    delay_a: Delay,
+    delay_b: Delay,
+    // Outputs of the previous sample, delayed by one sample less than requested
+    // since the feedback loop itself adds one sample.
+    fed_a: f64,
+    fed_b: f64,
+}
+
+impl ChunkProcessor for Race {
+    fn process(&mut self, waveforms: &mut [Vec<f64>]) {
+        let mut wave_a = std::mem::take(&mut waveforms[self.channel_a]);
+        let mut wave_b = std::mem::take(&mut waveforms[self.channel_b]);
+        for (xa, xb) in wave_a.iter_mut().zip(wave_b.iter_mut()) {
+            *xa -= self.gain * self.fed_b;
+            *xb -= self.gain * self.fed_a;
+            let mut a = [*xa];
+            let mut b = [*xb];
+            self.delay_a.process(&mut a);
+            self.delay_b.process(&mut b);
+            self.fed_a = a[0];
+            self.fed_b = b[0];
+        }
+        waveforms[self.channel_a] = wave_a;
+        waveforms[self.channel_b] = wave_b;
+    }
+}
+
+pub(crate) fn build(processor: &Processor, samplerate: usize) -> Box<dyn ChunkProcessor> {
+    match processor {
+        Processor::Compressor { parameters, .. } => Box::new(Compressor {
+            estimator: LoudnessEstimator::new(parameters.attack, parameters.release, samplerate),
+            monitor: channel_list(&parameters.monitor_channels, parameters.channels),
+            process: channel_list(&parameters.process_channels, parameters.channels),
+            threshold: parameters.threshold,
+            factor: parameters.factor,
+            makeup_gain: parameters.makeup_gain.unwrap_or(0.0),
+            clip: parameters.clip_limit.map(|limit| {
+                (
+                    10.0_f64.powf(limit / 20.0),
+                    parameters.soft_clip.unwrap_or(false),
+                )
+            }),
+        }),
+        Processor::NoiseGate { parameters, .. } => Box::new(NoiseGate {
+            estimator: LoudnessEstimator::new(parameters.attack, parameters.release, samplerate),
+            monitor: channel_list(&parameters.monitor_channels, parameters.channels),
+            process: channel_list(&parameters.process_channels, parameters.channels),
+            threshold: parameters.threshold,
+            attenuation: 10.0_f64.powf(-parameters.attenuation / 20.0),
+        }),
+        Processor::RACE { parameters, .. } => {
+            let samples = parameters
+                .delay_unit
+                .unwrap_or(TimeUnit::Milliseconds)
+                .to_samples(parameters.delay, samplerate);
+            let subsample = parameters.subsample_delay.unwrap_or(false);
+            let samples = if subsample { samples } else { samples.round() };
+            let delay = || Delay::new((samples - 1.0).max(0.0), subsample);
+            Box::new(Race {
+                channel_a: parameters.channel_a,
+                channel_b: parameters.channel_b,
+                gain: 10.0_f64.powf(-parameters.attenuation / 20.0),
+                delay_a: delay(),
+                delay_b: delay(),
+                fed_a: 0.0,
+                fed_b: 0.0,
+            })
+        }
+    }
+}
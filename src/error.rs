@@ -0,0 +1,750 @@
+use std::fmt;
+
+use serde_json::Value as Schema;
+use serde_yaml::Value;
+
+use crate::edit::{locate, path_string, Segment};
+use crate::schema::configuration_json_schema;
+
+/// An untagged variant that was tried, named by its fields.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VariantMismatch {
+    pub fields: Vec<String>,
+    pub missing: Vec<String>,
+    pub unknown: Vec<String>,
+}
+
+impl fmt::Display for VariantMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{{}}}", self.fields.join(", "))?;
+        let mut problems = Vec::new();
+        if !self.missing.is_empty() {
+            problems.push(format!("missing {}", quoted(&self.missing)));
+        }
+        for unknown in &self.unknown {
+            match suggest(unknown, self.missing.iter()) {
+                Some(suggestion) => problems.push(format!(
+                    "unknown `{}` (did you mean `{}`?)",
+                    unknown, suggestion
+                )),
+                None => problems.push(format!("unknown `{}`", unknown)),
+            }
+        }
+        if !problems.is_empty() {
+            write!(f, ": {}", problems.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigErrorKind {
+    /// The text could not be read or is not valid YAML.
+    Syntax(String),
+    MissingField(String),
+    UnknownField {
+        field: String,
+        suggestion: Option<String>,
+    },
+    InvalidType {
+        expected: String,
+        found: String,
+    },
+    /// A `type` tag or a string value that is not one of the allowed names.
+    UnknownVariant {
+        found: String,
+        expected: Vec<String>,
+    },
+    /// None of the variants of an untagged enum, such as `PeakingWidth`, matched.
+    NoMatchingVariant(Vec<VariantMismatch>),
+    /// A problem that could not be narrowed down, with the message from serde.
+    Other(String),
+}
+
+impl ConfigErrorKind {
+    /// The likely intended name for a misspelled field or variant.
+    pub fn suggestion(&self) -> Option<String> {
+        match self {
+            ConfigErrorKind::UnknownField { suggestion, .. } => suggestion.clone(),
+            ConfigErrorKind::UnknownVariant { found, expected } => suggest(found, expected.iter()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ConfigErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigErrorKind::Syntax(message) | ConfigErrorKind::Other(message) => {
+                write!(f, "{}", message)
+            }
+            ConfigErrorKind::MissingField(field) => write!(f, "missing field `{}`", field),
+            ConfigErrorKind::UnknownField { field, .. } => {
+                write!(f, "unknown field `{}`", field)?;
+                if let Some(suggestion) = self.suggestion() {
+                    write!(f, ", did you mean `{}`?", suggestion)?;
+                }
+                Ok(())
+            }
+            ConfigErrorKind::InvalidType { expected, found } => {
+                write!(f, "invalid type: expected {}, found {}", expected, found)
+            }
+            ConfigErrorKind::UnknownVariant { found, expected } => {
+                write!(f, "unknown variant `{}`", found)?;
+                match self.suggestion() {
+                    Some(suggestion) => write!(f, ", did you mean `{}`?", suggestion),
+                    None => write!(f, ", expected one of {}", quoted(expected)),
+                }
+            }
+            ConfigErrorKind::NoMatchingVariant(variants) => {
+                write!(f, "no variant matches, tried ")?;
+                let tried: Vec<String> = variants.iter().map(|v| v.to_string()).collect();
+                write!(f, "{}", tried.join("; "))
+            }
+        }
+    }
+}
+
+/// Error from reading a config, pointing at the offending key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigError {
+    pub kind: ConfigErrorKind,
+    /// Key path such as `filters.bass.parameters`, empty for the document itself.
+    pub path: String,
+    /// 1-based position in the YAML text, when known.
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.path.is_empty() {
+            write!(f, "{}", self.path)?;
+        }
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            if !self.path.is_empty() {
+                write!(f, " ")?;
+            }
+            write!(f, "at line {} column {}", line, column)?;
+        }
+        if !self.path.is_empty() || self.line.is_some() {
+            write!(f, ": ")?;
+        }
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl ConfigError {
+    /// Explain why `text` could not be read as a `Configuration`.
+    /// `error` is the original serde error, used when the problem can not be narrowed down.
+    pub fn from_yaml(text: &str, error: serde_yaml::Error) -> Self {
+        let value: Value = match serde_yaml::from_str(text) {
+            Ok(value) => value,
+            Err(syntax) => return ConfigError::with_location(ConfigErrorKind::Syntax, &syntax),
+        };
+        match diagnose(&value) {
+            Some(problem) => {
+                let (line, column) = locate(text, &problem.path)
+                    .or_else(|| error.location().map(|l| (l.line(), l.column())))
+                    .unzip();
+                ConfigError {
+                    kind: problem.kind,
+                    path: path_string(&problem.path),
+                    line,
+                    column,
+                }
+            }
+            None => ConfigError::with_location(ConfigErrorKind::Other, &error),
+        }
+    }
+
+    /// Explain why `value` could not be read as a `Configuration`, without a position.
+    pub fn from_value(value: &Value, error: serde_yaml::Error) -> Self {
+        let (kind, path) = match diagnose(value) {
+            Some(problem) => (problem.kind, path_string(&problem.path)),
+            None => (ConfigErrorKind::Other(error.to_string()), String::new()),
+        };
+        ConfigError {
+            kind,
+            path,
+            line: None,
+            column: None,
+        }
+    }
+
+    // Wrap a serde error message, the position is moved out of the message.
+    fn with_location(kind: fn(String) -> ConfigErrorKind, error: &serde_yaml::Error) -> Self {
+        let location = error.location();
+        let mut message = error.to_string();
+        if let Some(location) = &location {
+            let suffix = format!(" at line {} column {}", location.line(), location.column());
+            message = message.replacen(&suffix, "", 1);
+        }
+        ConfigError {
+            kind: kind(message),
+            path: String::new(),
+            line: location.as_ref().map(|l| l.line()),
+            column: location.as_ref().map(|l| l.column()),
+        }
+    }
+}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(error: std::io::Error) -> Self {
+        ConfigError {
+            kind: ConfigErrorKind::Syntax(error.to_string()),
+            path: String::new(),
+            line: None,
+            column: None,
+        }
+    }
+}
+
+// --- Diagnosis ---
+
+// Serde only says that a value did not match, so the value is checked again
+// against the JSON Schema of `Configuration` to find out where and why.
+
+struct Problem {
+    path: Vec<Segment>,
+    kind: ConfigErrorKind,
+}
+
+fn diagnose(value: &Value) -> Option<Problem> {
+    let schema = configuration_json_schema();
+    let checker = Checker { root: &schema };
+    checker.check(&schema, value, &[], &[])
+}
+
+static ANY: Schema = Schema::Bool(true);
+
+fn problem(path: &[Segment], segment: Option<Segment>, kind: ConfigErrorKind) -> Option<Problem> {
+    let mut path = path.to_vec();
+    path.extend(segment);
+    Some(Problem { path, kind })
+}
+
+fn child(path: &[Segment], segment: Segment) -> Vec<Segment> {
+    let mut path = path.to_vec();
+    path.push(segment);
+    path
+}
+
+fn key_string(key: &Value) -> String {
+    match key.as_str() {
+        Some(key) => key.to_string(),
+        None => serde_yaml::to_string(key)
+            .map(|text| text.trim_end().to_string())
+            .unwrap_or_default(),
+    }
+}
+
+fn quoted(names: &[String]) -> String {
+    let names: Vec<String> = names.iter().map(|name| format!("`{}`", name)).collect();
+    names.join(", ")
+}
+
+fn type_names(schema: &Schema) -> Vec<&str> {
+    match schema.get("type") {
+        Some(Schema::String(name)) => vec![name.as_str()],
+        Some(Schema::Array(names)) => names.iter().filter_map(Schema::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_bool(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "string" => value.is_string(),
+        "array" => value.is_sequence(),
+        "object" => value.is_mapping(),
+        _ => true,
+    }
+}
+
+fn describe_type(name: &str) -> &'static str {
+    match name {
+        "boolean" => "a boolean",
+        "integer" => "an integer",
+        "number" => "a number",
+        "string" => "a string",
+        "array" => "a sequence",
+        "object" => "a mapping",
+        _ => "a value",
+    }
+}
+
+fn describe_value(value: &Value) -> String {
+    let text = match value {
+        Value::Null => "nothing",
+        Value::Bool(_) => "a boolean",
+        Value::Number(number) if number.as_i64().is_some_and(|n| n < 0) => "a negative integer",
+        Value::Number(number) if number.is_f64() => "a number",
+        Value::Number(_) => "an integer",
+        Value::String(_) => "a string",
+        Value::Sequence(_) => "a sequence",
+        Value::Mapping(_) => "a mapping",
+        Value::Tagged(_) => "a tagged value",
+    };
+    text.to_string()
+}
+
+fn invalid_type(path: &[Segment], expected: &[&str], value: &Value) -> Option<Problem> {
+    let expected: Vec<&str> = expected
+        .iter()
+        .filter(|name| **name != "null")
+        .map(|name| describe_type(name))
+        .collect();
+    problem(
+        path,
+        None,
+        ConfigErrorKind::InvalidType {
+            expected: expected.join(" or "),
+            found: describe_value(value),
+        },
+    )
+}
+
+struct Checker<'a> {
+    root: &'a Schema,
+}
+
+impl<'a> Checker<'a> {
+    fn resolve(&self, mut schema: &'a Schema) -> &'a Schema {
+        while let Some(reference) = schema.get("$ref").and_then(Schema::as_str) {
+            schema = reference
+                .strip_prefix("#/$defs/")
+                .and_then(|name| self.root["$defs"].get(name))
+                .unwrap_or(&ANY);
+        }
+        schema
+    }
+
+    fn check(
+        &self,
+        schema: &'a Schema,
+        value: &Value,
+        path: &[Segment],
+        extra: &[String],
+    ) -> Option<Problem> {
+        let schema = self.resolve(schema);
+        if let Some(options) = schema.get("oneOf").and_then(Schema::as_array) {
+            return self.check_one_of(options, value, path, extra);
+        }
+        if let Some(parts) = schema.get("allOf").and_then(Schema::as_array) {
+            // Fields of the other parts are not unknown to each part.
+            let mut extra = extra.to_vec();
+            for part in parts {
+                extra.extend(self.property_names(part));
+            }
+            return parts
+                .iter()
+                .find_map(|part| self.check(part, value, path, &extra));
+        }
+        let types = type_names(schema);
+        if value.is_null() && types.contains(&"null") {
+            return None;
+        }
+        if !types.is_empty() && !types.iter().any(|name| has_type(value, name)) {
+            return invalid_type(path, &types, value);
+        }
+        if types.contains(&"integer")
+            && schema.get("minimum").and_then(Schema::as_f64) == Some(0.0)
+            && value.as_i64().is_some_and(|n| n < 0)
+        {
+            return problem(
+                path,
+                None,
+                ConfigErrorKind::InvalidType {
+                    expected: "an unsigned integer".to_string(),
+                    found: describe_value(value),
+                },
+            );
+        }
+        if let Some(allowed) = schema.get("enum").and_then(Schema::as_array) {
+            let allowed: Vec<String> = allowed
+                .iter()
+                .filter_map(|name| name.as_str().map(str::to_string))
+                .collect();
+            let found = key_string(value);
+            if !allowed.contains(&found) {
+                return problem(
+                    path,
+                    None,
+                    ConfigErrorKind::UnknownVariant {
+                        found,
+                        expected: allowed,
+                    },
+                );
+            }
+        }
+        match value {
+            Value::Mapping(map) => self.check_object(schema, map, path, extra),
+            Value::Sequence(items) => {
+                let item_schema = schema.get("items")?;
+                items.iter().enumerate().find_map(|(idx, item)| {
+                    self.check(item_schema, item, &child(path, Segment::Index(idx)), &[])
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn check_object(
+        &self,
+        schema: &'a Schema,
+        map: &serde_yaml::Mapping,
+        path: &[Segment],
+        extra: &[String],
+    ) -> Option<Problem> {
+        let properties = schema.get("properties").and_then(Schema::as_object);
+        let additional = schema.get("additionalProperties");
+        let closed = additional == Some(&Schema::Bool(false));
+        let keys: Vec<String> = map.keys().map(key_string).collect();
+        let missing: Vec<String> = schema
+            .get("required")
+            .and_then(Schema::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Schema::as_str)
+            .filter(|field| !keys.iter().any(|key| key == field))
+            .map(str::to_string)
+            .collect();
+        for (key, value) in keys.iter().zip(map.values()) {
+            let value_schema = properties
+                .and_then(|properties| properties.get(key))
+                .or(additional.filter(|schema| schema.is_object()));
+            if let Some(value_schema) = value_schema {
+                let found = self.check(
+                    value_schema,
+                    value,
+                    &child(path, Segment::Key(key.clone())),
+                    &[],
+                );
+                if found.is_some() {
+                    return found;
+                }
+                continue;
+            }
+            if extra.contains(key) {
+                continue;
+            }
+            // An unknown key close to a missing one is most likely misspelled,
+            // serde ignores other unknown keys unless the type denies them.
+            let suggestion = suggest(key, missing.iter());
+            if suggestion.is_some() || closed {
+                let unused = properties
+                    .into_iter()
+                    .flat_map(|properties| properties.keys())
+                    .filter(|field| !keys.contains(field));
+                return problem(
+                    path,
+                    Some(Segment::Key(key.clone())),
+                    ConfigErrorKind::UnknownField {
+                        field: key.clone(),
+                        suggestion: suggestion.or_else(|| suggest(key, unused)),
+                    },
+                );
+            }
+        }
+        let field = missing.into_iter().next()?;
+        problem(path, None, ConfigErrorKind::MissingField(field))
+    }
+
+    fn check_one_of(
+        &self,
+        options: &'a [Schema],
+        value: &Value,
+        path: &[Segment],
+        extra: &[String],
+    ) -> Option<Problem> {
+        let options: Vec<&Schema> = options
+            .iter()
+            .map(|option| self.resolve(option))
+            .filter(|option| type_names(option) != ["null"])
+            .collect();
+        if value.is_null() && options.len() < 2 {
+            // An optional value, `null` was one of the options.
+            return None;
+        }
+        if let [option] = options[..] {
+            return self.check(option, value, path, extra);
+        }
+        let tags: Option<Vec<String>> = options.iter().map(|option| self.tag(option)).collect();
+        if let Some(tags) = tags {
+            let Some(map) = value.as_mapping() else {
+                return invalid_type(path, &["object"], value);
+            };
+            let Some(tag) = map.get("type") else {
+                return problem(
+                    path,
+                    None,
+                    ConfigErrorKind::MissingField("type".to_string()),
+                );
+            };
+            let found = key_string(tag);
+            return match tags.iter().position(|name| *name == found) {
+                Some(idx) => self.check(options[idx], value, path, extra),
+                None => problem(
+                    path,
+                    Some(Segment::Key("type".to_string())),
+                    ConfigErrorKind::UnknownVariant {
+                        found,
+                        expected: tags,
+                    },
+                ),
+            };
+        }
+        let problems: Vec<Option<Problem>> = options
+            .iter()
+            .map(|option| self.check(option, value, path, extra))
+            .collect();
+        if problems.iter().any(Option::is_none) {
+            return None;
+        }
+        let Some(map) = value.as_mapping() else {
+            // Prefer the problem of a variant of the right kind.
+            let mut problems = problems.into_iter().flatten();
+            let first = problems.next();
+            return problems
+                .find(|p| !matches!(p.kind, ConfigErrorKind::InvalidType { .. }))
+                .or(first);
+        };
+        let mismatches: Vec<VariantMismatch> = options
+            .iter()
+            .map(|option| self.mismatch(option, map, extra))
+            .collect();
+        // A single variant with all its fields present is what was meant,
+        // its problem is further down.
+        let complete: Vec<usize> = (0..mismatches.len())
+            .filter(|&idx| mismatches[idx].missing.is_empty())
+            .collect();
+        if let [idx] = complete[..] {
+            return problems.into_iter().nth(idx).flatten();
+        }
+        problem(path, None, ConfigErrorKind::NoMatchingVariant(mismatches))
+    }
+
+    // The value of the `type` property of a variant of a tagged enum.
+    fn tag(&self, schema: &'a Schema) -> Option<String> {
+        let schema = self.resolve(schema);
+        if let Some(parts) = schema.get("allOf").and_then(Schema::as_array) {
+            return parts.iter().find_map(|part| self.tag(part));
+        }
+        match schema
+            .pointer("/properties/type/enum")?
+            .as_array()?
+            .as_slice()
+        {
+            [Schema::String(tag)] => Some(tag.clone()),
+            _ => None,
+        }
+    }
+
+    fn property_names(&self, schema: &'a Schema) -> Vec<String> {
+        let schema = self.resolve(schema);
+        let mut names: Vec<String> = schema
+            .get("properties")
+            .and_then(Schema::as_object)
+            .map(|properties| properties.keys().cloned().collect())
+            .unwrap_or_default();
+        for key in ["allOf", "oneOf"] {
+            for part in schema
+                .get(key)
+                .and_then(Schema::as_array)
+                .into_iter()
+                .flatten()
+            {
+                names.extend(self.property_names(part));
+            }
+        }
+        names
+    }
+
+    fn required_names(&self, schema: &'a Schema) -> Vec<String> {
+        let schema = self.resolve(schema);
+        let mut names: Vec<String> = schema
+            .get("required")
+            .and_then(Schema::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|name| name.as_str().map(str::to_string))
+            .collect();
+        for part in schema
+            .get("allOf")
+            .and_then(Schema::as_array)
+            .into_iter()
+            .flatten()
+        {
+            names.extend(self.required_names(part));
+        }
+        names
+    }
+
+    fn mismatch(
+        &self,
+        schema: &'a Schema,
+        map: &serde_yaml::Mapping,
+        extra: &[String],
+    ) -> VariantMismatch {
+        let keys: Vec<String> = map.keys().map(key_string).collect();
+        let known = self.property_names(schema);
+        let mut fields = self.required_names(schema);
+        for name in &known {
+            if !fields.contains(name) {
+                fields.push(name.clone());
+            }
+        }
+        let missing = self
+            .required_names(schema)
+            .into_iter()
+            .filter(|field| !keys.contains(field))
+            .collect();
+        let unknown = keys
+            .into_iter()
+            .filter(|key| !known.contains(key) && !extra.contains(key))
+            .collect();
+        VariantMismatch {
+            fields,
+            missing,
+            unknown,
+        }
+    }
+}
+
+// --- Suggestions ---
+
+// Edits needed to turn `a` into `b`, swapping two neighbouring letters counts as one.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut table = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in table.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in table[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (table[i - 1][j] + 1)
+                .min(table[i][j - 1] + 1)
+                .min(table[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(table[i - 2][j - 2] + 1);
+            }
+            table[i][j] = best;
+        }
+    }
+    table[a.len()][b.len()]
+}
+
+/// The candidate closest to `word`, if it is close enough to be a misspelling.
+fn suggest<'s>(word: &str, candidates: impl Iterator<Item = &'s String>) -> Option<String> {
+    let word = word.to_lowercase();
+    let limit = (word.chars().count() / 3).max(1);
+    candidates
+        .map(|candidate| (edit_distance(&word, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Configuration;
+
+    const DEVICES: &str = r#"---
+devices:
+  samplerate: 48000
+  chunksize: 1024
+  capture:
+    type: Stdin
+    channels: 2
+    format: S16_LE
+  playback:
+    type: Stdout
+    channels: 2
+    format: S16_LE
+"#;
+
+    fn parse_error(yaml: &str) -> ConfigError {
+        Configuration::from_yaml_string(&format!("{}{}", DEVICES, yaml)).unwrap_err()
+    }
+
+    #[test]
+    fn test_untagged_variants() {
+        let error = parse_error(
+            r#"filters:
+  bass:
+    type: Biquad
+    parameters:
+      type: Peaking
+      freq: 100
+      Q: 0.7
+      gain: 3
+"#,
+        );
+        assert_eq!(error.path, "filters.bass.parameters");
+        assert_eq!((error.line, error.column), (Some(16), Some(5)));
+        let ConfigErrorKind::NoMatchingVariant(variants) = &error.kind else {
+            panic!("unexpected error {:?}", error);
+        };
+        assert_eq!(variants[0].missing, vec!["q"]);
+        assert_eq!(variants[1].missing, vec!["bandwidth"]);
+        assert_eq!(variants[1].unknown, vec!["Q"]);
+        assert_eq!(
+            error.to_string(),
+            "filters.bass.parameters at line 16 column 5: no variant matches, tried \
+             {freq, q, gain}: missing `q`, unknown `Q` (did you mean `q`?); \
+             {freq, bandwidth, gain}: missing `bandwidth`, unknown `Q`"
+        );
+    }
+
+    #[test]
+    fn test_suggestions_and_types() {
+        let error = parse_error("filters:\n  vol:\n    type: Gian\n");
+        assert_eq!(error.path, "filters.vol.type");
+        assert!(
+            matches!(&error.kind, ConfigErrorKind::UnknownVariant { found, .. } if found == "Gian")
+        );
+        assert_eq!(error.kind.suggestion(), Some("Gain".to_string()));
+
+        let error =
+            parse_error("filters:\n  vol:\n    type: Gain\n    parameter:\n      gain: 1\n");
+        assert_eq!(error.path, "filters.vol.parameter");
+        assert_eq!(error.line, Some(16));
+        assert!(error.to_string().ends_with("did you mean `parameters`?"));
+
+        let error = parse_error(
+            "pipeline:\n  - type: Filter\n    channels: [0]\n    names: [vol]\n    bypased: true\n",
+        );
+        assert_eq!(error.path, "pipeline[0].bypased");
+        assert_eq!(
+            error.kind,
+            ConfigErrorKind::UnknownField {
+                field: "bypased".to_string(),
+                suggestion: Some("bypassed".to_string()),
+            }
+        );
+
+        let error =
+            parse_error("filters:\n  vol:\n    type: Gain\n    parameters:\n      gain: loud\n");
+        assert_eq!(
+            error.to_string(),
+            "filters.vol.parameters.gain at line 17 column 7: \
+             invalid type: expected a number, found a string"
+        );
+
+        let error = Configuration::from_yaml_string("devices: [\n").unwrap_err();
+        assert!(matches!(error.kind, ConfigErrorKind::Syntax(_)));
+        assert_eq!(error.line, Some(2));
+    }
+}
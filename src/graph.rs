@@ -0,0 +1,422 @@
+use std::fmt::Write;
+
+use crate::types::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+    Capture,
+    Filter,
+    Mixer,
+    Processor,
+    Playback,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphNode {
+    pub id: String,
+    pub label: String,
+    pub kind: NodeKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub label: Option<String>,
+    /// The channel is only monitored by a processor, for example the sidechain
+    /// of a compressor, and continues unchanged.
+    pub monitor: bool,
+}
+
+/// Signal flow of the pipeline with one node per filter and channel.
+/// Mixers and processors are single nodes that the channels run through.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SignalGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+// The node a channel currently comes from, and the label for the edge leaving it.
+type Source = (String, Option<String>);
+
+impl SignalGraph {
+    fn node(&mut self, id: String, label: String, kind: NodeKind) -> String {
+        self.nodes.push(GraphNode {
+            id: id.clone(),
+            label,
+            kind,
+        });
+        id
+    }
+
+    fn edge(&mut self, source: &Source, to: &str, channel: usize, monitor: bool) {
+        let label = source.1.clone().or_else(|| {
+            let into_mixer = self
+                .nodes
+                .iter()
+                .any(|n| n.id == to && n.kind == NodeKind::Mixer);
+            into_mixer.then(|| channel.to_string())
+        });
+        self.edges.push(GraphEdge {
+            from: source.0.clone(),
+            to: to.to_string(),
+            label,
+            monitor,
+        });
+    }
+
+    /// Render as a Graphviz digraph, for `dot -Tsvg`.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph pipeline {\n    rankdir=LR;\n");
+        for node in &self.nodes {
+            let shape = match node.kind {
+                NodeKind::Capture | NodeKind::Playback => "ellipse",
+                NodeKind::Filter => "box",
+                NodeKind::Mixer => "hexagon",
+                NodeKind::Processor => "box3d",
+            };
+            let _ = writeln!(
+                dot,
+                "    {} [label=\"{}\", shape={}];",
+                node.id,
+                dot_escape(&node.label),
+                shape
+            );
+        }
+        for edge in &self.edges {
+            let mut attributes = Vec::new();
+            if let Some(label) = &edge.label {
+                attributes.push(format!("label=\"{}\"", dot_escape(label)));
+            }
+            if edge.monitor {
+                attributes.push("style=dashed".to_string());
+            }
+            let _ = write!(dot, "    {} -> {}", edge.from, edge.to);
+            if !attributes.is_empty() {
+                let _ = write!(dot, " [{}]", attributes.join(", "));
+            }
+            dot.push_str(";\n");
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Render as a Mermaid flowchart, for Markdown documents.
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = String::from("flowchart LR\n");
+        for node in &self.nodes {
+            let label = mermaid_escape(&node.label);
+            let _ = match node.kind {
+                NodeKind::Capture | NodeKind::Playback => {
+                    writeln!(mermaid, "    {}([\"{}\"])", node.id, label)
+                }
+                NodeKind::Filter => writeln!(mermaid, "    {}[\"{}\"]", node.id, label),
+                NodeKind::Mixer => writeln!(mermaid, "    {}{{{{\"{}\"}}}}", node.id, label),
+                NodeKind::Processor => writeln!(mermaid, "    {}[[\"{}\"]]", node.id, label),
+            };
+        }
+        for edge in &self.edges {
+            let arrow = match (&edge.label, edge.monitor) {
+                (None, false) => "-->".to_string(),
+                (None, true) => "-.->".to_string(),
+                (Some(label), false) => format!("-->|\"{}\"|", mermaid_escape(label)),
+                (Some(label), true) => format!("-.->|\"{}\"|", mermaid_escape(label)),
+            };
+            let _ = writeln!(mermaid, "    {} {} {}", edge.from, arrow, edge.to);
+        }
+        mermaid
+    }
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+// Channels a processor changes, and the ones it only listens to.
+fn processor_channels(processor: &Processor, channels: usize) -> (Vec<usize>, Vec<usize>) {
+    let all = || (0..channels).collect::<Vec<usize>>();
+    let (process, monitor) = match processor {
+        Processor::Compressor { parameters, .. } => (
+            parameters.process_channels.clone().unwrap_or_else(all),
+            parameters.monitor_channels.clone().unwrap_or_else(all),
+        ),
+        Processor::NoiseGate { parameters, .. } => (
+            parameters.process_channels.clone().unwrap_or_else(all),
+            parameters.monitor_channels.clone().unwrap_or_else(all),
+        ),
+        Processor::RACE { parameters, .. } => {
+            (vec![parameters.channel_a, parameters.channel_b], Vec::new())
+        }
+    };
+    let monitor = monitor
+        .into_iter()
+        .filter(|channel| !process.contains(channel))
+        .collect();
+    (process, monitor)
+}
+
+impl Configuration {
+    /// Trace every channel from capture to playback through the active pipeline steps.
+    /// Bypassed steps, and steps that refer to unknown mixers or processors, are left out.
+    /// When the capture channel count is unknown the input count of the first mixer,
+    /// or else the playback channel count, is used.
+    pub fn signal_graph(&self) -> SignalGraph {
+        let mut graph = SignalGraph::default();
+        let steps: Vec<(usize, &PipelineStep)> = self
+            .pipeline
+            .iter()
+            .flatten()
+            .enumerate()
+            .filter(|(_, step)| !step.is_bypassed())
+            .collect();
+        let capture = self.devices.capture.channels().unwrap_or_else(|| {
+            steps
+                .iter()
+                .find_map(|(_, step)| match step {
+                    PipelineStep::Mixer(step) => self
+                        .mixers
+                        .as_ref()
+                        .and_then(|m| m.get(&step.name))
+                        .map(|m| m.channels.r#in),
+                    _ => None,
+                })
+                .unwrap_or_else(|| self.devices.playback.channels())
+        });
+        let labels = self.devices.capture.labels().unwrap_or_default();
+        let mut sources: Vec<Source> = (0..capture)
+            .map(|channel| {
+                let label = match labels.get(channel) {
+                    Some(Some(label)) => format!("capture {}: {}", channel, label),
+                    _ => format!("capture {}", channel),
+                };
+                let id = graph.node(format!("capture_{}", channel), label, NodeKind::Capture);
+                (id, None)
+            })
+            .collect();
+
+        for (index, step) in steps {
+            match step {
+                PipelineStep::Filter(step) => {
+                    let channels = match &step.channels {
+                        Some(channels) => channels.clone(),
+                        None => (0..sources.len()).collect(),
+                    };
+                    let count = sources.len();
+                    for channel in channels.into_iter().filter(|c| *c < count) {
+                        for (n, name) in step.names.iter().enumerate() {
+                            let id = graph.node(
+                                format!("p{}_c{}_{}", index, channel, n),
+                                name.clone(),
+                                NodeKind::Filter,
+                            );
+                            graph.edge(&sources[channel], &id, channel, false);
+                            sources[channel] = (id, None);
+                        }
+                    }
+                }
+                PipelineStep::Mixer(step) => {
+                    let Some(mixer) = self.mixers.as_ref().and_then(|m| m.get(&step.name)) else {
+                        continue;
+                    };
+                    let id = graph.node(format!("p{}", index), step.name.clone(), NodeKind::Mixer);
+                    let matrix = mixer.matrix();
+                    for (channel, source) in sources.iter().enumerate() {
+                        let used = channel < matrix.inputs()
+                            && matrix.rows().iter().any(|row| row[channel] != 0.0);
+                        if used {
+                            graph.edge(source, &id, channel, false);
+                        }
+                    }
+                    let labels = mixer.labels.as_deref().unwrap_or_default();
+                    sources = (0..mixer.channels.out)
+                        .map(|channel| {
+                            let label = match labels.get(channel) {
+                                Some(Some(label)) => format!("{}: {}", channel, label),
+                                _ => channel.to_string(),
+                            };
+                            (id.clone(), Some(label))
+                        })
+                        .collect();
+                }
+                PipelineStep::Processor(step) => {
+                    let Some(processor) = self.processors.as_ref().and_then(|p| p.get(&step.name))
+                    else {
+                        continue;
+                    };
+                    let id = graph.node(
+                        format!("p{}", index),
+                        step.name.clone(),
+                        NodeKind::Processor,
+                    );
+                    let (process, monitor) = processor_channels(processor, sources.len());
+                    for channel in monitor.into_iter().filter(|c| *c < sources.len()) {
+                        graph.edge(&sources[channel], &id, channel, true);
+                    }
+                    let count = sources.len();
+                    for channel in process.into_iter().filter(|c| *c < count) {
+                        graph.edge(&sources[channel], &id, channel, false);
+                        sources[channel] = (id.clone(), Some(channel.to_string()));
+                    }
+                }
+            }
+        }
+
+        for (channel, source) in sources
+            .iter()
+            .enumerate()
+            .take(self.devices.playback.channels())
+        {
+            let id = graph.node(
+                format!("playback_{}", channel),
+                format!("playback {}", channel),
+                NodeKind::Playback,
+            );
+            graph.edge(source, &id, channel, false);
+        }
+        graph
+    }
+
+    /// The signal graph in Graphviz DOT format.
+    pub fn to_dot(&self) -> String {
+        self.signal_graph().to_dot()
+    }
+
+    /// The signal graph as a Mermaid flowchart.
+    pub fn to_mermaid(&self) -> String {
+        self.signal_graph().to_mermaid()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"---
+devices:
+  samplerate: 48000
+  chunksize: 1024
+  capture:
+    type: Stdin
+    channels: 2
+    format: S16_LE
+    labels: [L, R]
+  playback:
+    type: Stdout
+    channels: 3
+    format: S16_LE
+filters:
+  vol:
+    type: Gain
+    parameters:
+      gain: -3
+  lp:
+    type: Biquad
+    parameters:
+      type: Lowpass
+      freq: 80
+      q: 0.7
+mixers:
+  to21:
+    channels:
+      in: 2
+      out: 3
+    labels: [L, R, LFE]
+    mapping:
+      - dest: 0
+        sources:
+          - channel: 0
+      - dest: 1
+        sources:
+          - channel: 1
+      - dest: 2
+        sources:
+          - channel: 0
+          - channel: 1
+processors:
+  gate:
+    type: NoiseGate
+    parameters:
+      channels: 3
+      process_channels: [2]
+      monitor_channels: [0, 1]
+      attack: 0.1
+      release: 1.0
+      threshold: -50
+      attenuation: 30
+pipeline:
+  - type: Filter
+    channels: [0]
+    names: [vol]
+  - type: Mixer
+    name: to21
+  - type: Filter
+    channels: [2]
+    names: [vol, lp]
+  - type: Filter
+    channels: [1]
+    names: [lp]
+    bypassed: true
+  - type: Processor
+    name: gate
+"#;
+
+    #[test]
+    fn test_dot() {
+        let config = Configuration::from_yaml_string(CONFIG).unwrap();
+        assert_eq!(
+            config.to_dot(),
+            r#"digraph pipeline {
+    rankdir=LR;
+    capture_0 [label="capture 0: L", shape=ellipse];
+    capture_1 [label="capture 1: R", shape=ellipse];
+    p0_c0_0 [label="vol", shape=box];
+    p1 [label="to21", shape=hexagon];
+    p2_c2_0 [label="vol", shape=box];
+    p2_c2_1 [label="lp", shape=box];
+    p4 [label="gate", shape=box3d];
+    playback_0 [label="playback 0", shape=ellipse];
+    playback_1 [label="playback 1", shape=ellipse];
+    playback_2 [label="playback 2", shape=ellipse];
+    capture_0 -> p0_c0_0;
+    p0_c0_0 -> p1 [label="0"];
+    capture_1 -> p1 [label="1"];
+    p1 -> p2_c2_0 [label="2: LFE"];
+    p2_c2_0 -> p2_c2_1;
+    p1 -> p4 [label="0: L", style=dashed];
+    p1 -> p4 [label="1: R", style=dashed];
+    p2_c2_1 -> p4;
+    p1 -> playback_0 [label="0: L"];
+    p1 -> playback_1 [label="1: R"];
+    p4 -> playback_2 [label="2"];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_mermaid() {
+        let yaml = CONFIG.replace("labels: [L, R]\n", "labels: [\"L \\\"main\\\"\", null]\n");
+        let config = Configuration::from_yaml_string(&yaml).unwrap();
+        let mermaid = config.to_mermaid();
+        let lines: Vec<&str> = mermaid.lines().collect();
+        assert_eq!(lines[0], "flowchart LR");
+        assert_eq!(
+            lines[1],
+            "    capture_0([\"capture 0: L #quot;main#quot;\"])"
+        );
+        assert_eq!(lines[2], "    capture_1([\"capture 1\"])");
+        assert!(lines.contains(&"    p1{{\"to21\"}}"));
+        assert!(lines.contains(&"    p4[[\"gate\"]]"));
+        assert!(lines.contains(&"    p1 -.->|\"0: L\"| p4"));
+        assert!(lines.contains(&"    p2_c2_1 --> p4"));
+        assert_eq!(lines.last(), Some(&"    p4 -->|\"2\"| playback_2"));
+
+        let graph = config.signal_graph();
+        assert_eq!(graph.nodes.len(), 10);
+        assert_eq!(graph.edges.iter().filter(|e| e.monitor).count(), 2);
+    }
+}
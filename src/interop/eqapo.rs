@@ -0,0 +1,318 @@
+use std::fmt::Write;
+
+use super::{biquad_from_line, parse_filter_line, ExportError, ParametricEq, ParseError};
+use crate::types::*;
+
+fn parse_preamp(text: &str) -> Result<f64, String> {
+    let value = text.trim().strip_suffix("dB").unwrap_or(text).trim();
+    value
+        .parse::<f64>()
+        .map_err(|_| format!("invalid preamp '{}'", text.trim()))
+}
+
+/// Parse an Equalizer APO / AutoEQ `ParametricEQ.txt` file.
+/// Filters are named `<prefix>_preamp` and `<prefix>_1`, `<prefix>_2`, ... in file order.
+pub fn parse_eqapo(text: &str, prefix: &str) -> Result<ParametricEq, ParseError> {
+    let mut preamp: Option<f64> = None;
+    let mut biquads = Vec::new();
+    for (idx, raw) in text.lines().enumerate() {
+        let error = |message: String| ParseError {
+            line: idx + 1,
+            message,
+        };
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((command, rest)) = line.split_once(':') else {
+            return Err(error(format!("expected a command, found '{}'", line)));
+        };
+        let command = command.trim();
+        if command == "Preamp" {
+            *preamp.get_or_insert(0.0) += parse_preamp(rest).map_err(error)?;
+        } else if command == "Filter" || command.starts_with("Filter ") {
+            let filter_line = parse_filter_line(rest).map_err(error)?;
+            if let Some(biquad) = biquad_from_line(&filter_line).map_err(error)? {
+                biquads.push(biquad);
+            }
+        } else {
+            return Err(error(format!("unsupported command '{}'", command)));
+        }
+    }
+
+    let mut filters = Vec::new();
+    if let Some(gain) = preamp {
+        filters.push((
+            format!("{}_preamp", prefix),
+            Filter::Gain {
+                description: Some("Preamp".to_string()),
+                parameters: GainParameters {
+                    gain,
+                    inverted: None,
+                    mute: None,
+                    scale: Some(GainScale::Decibel),
+                },
+            },
+        ));
+    }
+    for (n, biquad) in biquads.into_iter().enumerate() {
+        filters.push((
+            format!("{}_{}", prefix, n + 1),
+            Filter::Biquad {
+                description: None,
+                parameters: biquad,
+            },
+        ));
+    }
+    Ok(ParametricEq::new(filters, None))
+}
+
+// Write a value with as few decimals as possible, ignoring rounding errors in the last
+// bit of computed values such as a summed preamp. Used for every number in the file,
+// so that the preamp and the filter lines are written with the same precision.
+fn number(value: f64) -> String {
+    (0..17)
+        .map(|decimals| format!("{:.*}", decimals, value))
+        .find(|text| {
+            text.parse::<f64>()
+                .is_ok_and(|parsed| (parsed - value).abs() <= f64::EPSILON * value.abs())
+        })
+        .unwrap_or_else(|| value.to_string())
+}
+
+fn filter_type(filter: &Filter) -> &'static str {
+    match filter {
+        Filter::Conv { .. } => "Conv",
+        Filter::Biquad { .. } => "Biquad",
+        Filter::BiquadCombo { .. } => "BiquadCombo",
+        Filter::Delay { .. } => "Delay",
+        Filter::Gain { .. } => "Gain",
+        Filter::Volume { .. } => "Volume",
+        Filter::Loudness { .. } => "Loudness",
+        Filter::Dither { .. } => "Dither",
+        Filter::DiffEq { .. } => "DiffEq",
+        Filter::Limiter { .. } => "Limiter",
+    }
+}
+
+fn biquad_type(parameters: &BiquadParameters) -> &'static str {
+    match parameters {
+        BiquadParameters::Free { .. } => "Free",
+        BiquadParameters::Highpass { .. } => "Highpass",
+        BiquadParameters::Lowpass { .. } => "Lowpass",
+        BiquadParameters::Peaking(_) => "Peaking",
+        BiquadParameters::Highshelf(_) => "Highshelf",
+        BiquadParameters::HighshelfFO { .. } => "HighshelfFO",
+        BiquadParameters::Lowshelf(_) => "Lowshelf",
+        BiquadParameters::LowshelfFO { .. } => "LowshelfFO",
+        BiquadParameters::HighpassFO { .. } => "HighpassFO",
+        BiquadParameters::LowpassFO { .. } => "LowpassFO",
+        BiquadParameters::Allpass(_) => "Allpass",
+        BiquadParameters::AllpassFO { .. } => "AllpassFO",
+        BiquadParameters::Bandpass(_) => "Bandpass",
+        BiquadParameters::Notch(_) => "Notch",
+        BiquadParameters::GeneralNotch(_) => "GeneralNotch",
+        BiquadParameters::LinkwitzTransform { .. } => "LinkwitzTransform",
+    }
+}
+
+fn shelf_line(kind: &str, steepness: &ShelfSteepness) -> Result<String, ExportError> {
+    match *steepness {
+        ShelfSteepness::Q { freq, q, gain } => Ok(format!(
+            "{}C Fc {} Hz Gain {} dB Q {}",
+            kind,
+            number(freq),
+            number(gain),
+            number(q)
+        )),
+        ShelfSteepness::Slope { freq, slope, gain } if slope == 6.0 || slope == 12.0 => {
+            Ok(format!(
+                "{} {}dB Fc {} Hz Gain {} dB",
+                kind,
+                number(slope),
+                number(freq),
+                number(gain)
+            ))
+        }
+        ShelfSteepness::Slope { slope, .. } => Err(ExportError(format!(
+            "shelf slope of {} dB/octave, only 6 and 12 are supported",
+            slope
+        ))),
+    }
+}
+
+fn biquad_line(parameters: &BiquadParameters) -> Result<String, ExportError> {
+    match *parameters {
+        BiquadParameters::Peaking(PeakingWidth::Q { freq, q, gain }) => Ok(format!(
+            "PK Fc {} Hz Gain {} dB Q {}",
+            number(freq),
+            number(gain),
+            number(q)
+        )),
+        BiquadParameters::Peaking(PeakingWidth::Bandwidth {
+            freq,
+            bandwidth,
+            gain,
+        }) => Ok(format!(
+            "PK Fc {} Hz Gain {} dB BW Oct {}",
+            number(freq),
+            number(gain),
+            number(bandwidth)
+        )),
+        BiquadParameters::Lowshelf(ref steepness) => shelf_line("LS", steepness),
+        BiquadParameters::Highshelf(ref steepness) => shelf_line("HS", steepness),
+        BiquadParameters::Lowpass { freq, q } => {
+            Ok(format!("LPQ Fc {} Hz Q {}", number(freq), number(q)))
+        }
+        BiquadParameters::Highpass { freq, q } => {
+            Ok(format!("HPQ Fc {} Hz Q {}", number(freq), number(q)))
+        }
+        BiquadParameters::Notch(NotchWidth::Q { freq, q }) => {
+            Ok(format!("NO Fc {} Hz Q {}", number(freq), number(q)))
+        }
+        BiquadParameters::Notch(NotchWidth::Bandwidth { freq, bandwidth }) => Ok(format!(
+            "NO Fc {} Hz BW Oct {}",
+            number(freq),
+            number(bandwidth)
+        )),
+        BiquadParameters::Allpass(NotchWidth::Q { freq, q }) => {
+            Ok(format!("AP Fc {} Hz Q {}", number(freq), number(q)))
+        }
+        BiquadParameters::Allpass(NotchWidth::Bandwidth { .. }) => Err(ExportError(
+            "Allpass biquads with a bandwidth have no Equalizer APO equivalent, use a Q"
+                .to_string(),
+        )),
+        ref other => Err(ExportError(format!(
+            "{} biquads have no Equalizer APO equivalent",
+            biquad_type(other)
+        ))),
+    }
+}
+
+/// Write filters in Equalizer APO format. Gain filters are summed into the preamp,
+/// biquads are written as numbered filter lines.
+pub fn to_eqapo<'a>(filters: impl IntoIterator<Item = &'a Filter>) -> Result<String, ExportError> {
+    let mut preamp = None;
+    let mut lines = Vec::new();
+    for filter in filters {
+        match filter {
+            Filter::Gain { parameters, .. } => {
+                let gain = parameters.linear_gain();
+                if gain <= 0.0 {
+                    return Err(ExportError(
+                        "muted or inverted gain can not be written as preamp".to_string(),
+                    ));
+                }
+                let decibels = match parameters.scale.unwrap_or(GainScale::Decibel) {
+                    GainScale::Decibel => parameters.gain,
+                    GainScale::Linear => 20.0 * gain.log10(),
+                };
+                *preamp.get_or_insert(0.0) += decibels;
+            }
+            Filter::Biquad { parameters, .. } => lines.push(biquad_line(parameters)?),
+            other => {
+                return Err(ExportError(format!(
+                    "{} filters have no Equalizer APO equivalent",
+                    filter_type(other)
+                )))
+            }
+        }
+    }
+    let mut out = String::new();
+    if let Some(gain) = preamp {
+        writeln!(out, "Preamp: {} dB", number(gain)).unwrap();
+    }
+    for (n, line) in lines.iter().enumerate() {
+        writeln!(out, "Filter {}: ON {}", n + 1, line).unwrap();
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AUTOEQ: &str = "Preamp: -6.2 dB
+Filter 1: ON LSC Fc 105 Hz Gain 5.8 dB Q 0.70
+Filter 2: ON PK Fc 3000 Hz Gain -3.2 dB Q 1.41
+Filter 3: OFF PK Fc 4000 Hz Gain 1.0 dB Q 2.00
+Filter 4: ON HSC Fc 10000 Hz Gain -2.0 dB Q 0.70
+Filter 5: ON LP Fc 18000 Hz
+Filter 6: ON NO Fc 50 Hz
+";
+
+    #[test]
+    fn test_parse_autoeq() {
+        let eq = parse_eqapo(AUTOEQ, "hp").unwrap();
+        let names: Vec<&str> = eq.filters.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["hp_preamp", "hp_1", "hp_2", "hp_3", "hp_4", "hp_5"]
+        );
+        assert_eq!(eq.step.names, names);
+        assert_eq!(
+            eq.filters[1].1,
+            Filter::Biquad {
+                description: None,
+                parameters: BiquadParameters::Lowshelf(ShelfSteepness::Q {
+                    freq: 105.0,
+                    q: 0.7,
+                    gain: 5.8
+                }),
+            }
+        );
+        assert!(matches!(
+            eq.filters[4].1,
+            Filter::Biquad {
+                parameters: BiquadParameters::Lowpass { freq, .. },
+                ..
+            } if freq == 18000.0
+        ));
+        assert!(matches!(
+            eq.filters[5].1,
+            Filter::Biquad {
+                parameters: BiquadParameters::Notch(NotchWidth::Q { q, .. }),
+                ..
+            } if q == 30.0
+        ));
+
+        let err = parse_eqapo("Preamp: -1 dB\nFilter 1: ON XX Fc 1 Hz\n", "x").unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+
+    #[test]
+    fn test_export_roundtrip() {
+        let eq = parse_eqapo(AUTOEQ, "hp").unwrap();
+        let text = to_eqapo(eq.filters.iter().map(|(_, f)| f)).unwrap();
+        assert!(text.starts_with("Preamp: -6.2 dB\nFilter 1: ON LSC Fc 105 Hz Gain 5.8 dB Q 0.7\n"));
+        let again = parse_eqapo(&text, "hp").unwrap();
+        assert_eq!(again, eq);
+
+        let delay = Filter::Delay {
+            description: None,
+            parameters: DelayParameters {
+                delay: 1.0,
+                unit: None,
+                subsample: None,
+            },
+        };
+        assert_eq!(
+            to_eqapo([&delay]).unwrap_err().to_string(),
+            "cannot export filter: Delay filters have no Equalizer APO equivalent"
+        );
+        let bandpass = Filter::biquad(BiquadParameters::Bandpass(NotchWidth::Q {
+            freq: 1000.0,
+            q: 2.0,
+        }));
+        assert_eq!(
+            to_eqapo([&bandpass]).unwrap_err().to_string(),
+            "cannot export filter: Bandpass biquads have no Equalizer APO equivalent"
+        );
+
+        // Computed values are written with the same precision as the filter lines.
+        let text = to_eqapo([&Filter::gain(-1.1), &Filter::gain(-2.2)]).unwrap();
+        assert_eq!(text, "Preamp: -3.3 dB\n");
+        let text = to_eqapo([&Filter::peaking(1000.0, 1.414, -3.25)]).unwrap();
+        assert_eq!(text, "Filter 1: ON PK Fc 1000 Hz Gain -3.25 dB Q 1.414\n");
+    }
+}
@@ -0,0 +1,193 @@
+mod eqapo;
+mod rew;
+
+pub use eqapo::*;
+pub use rew::*;
+
+use std::fmt;
+
+use crate::types::*;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportError(pub String);
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot export filter: {}", self.0)
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+/// Named filters imported from a text format, in the order they are applied,
+/// together with a pipeline step that applies them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParametricEq {
+    pub filters: Vec<(String, Filter)>,
+    pub step: PipelineStepFilter,
+}
+
+impl ParametricEq {
+    fn new(filters: Vec<(String, Filter)>, channels: Option<Vec<usize>>) -> Self {
+        let names = filters.iter().map(|(name, _)| name.clone()).collect();
+        ParametricEq {
+            filters,
+            step: PipelineStepFilter {
+                channels,
+                names,
+                description: None,
+                bypassed: None,
+            },
+        }
+    }
+
+    /// Add the filters to `config.filters` and append the step to the pipeline.
+    /// Existing filters with the same names are replaced.
+    pub fn add_to(&self, config: &mut Configuration) {
+        let filters = config.filters.get_or_insert_with(Default::default);
+        for (name, filter) in &self.filters {
+            filters.insert(name.clone(), filter.clone());
+        }
+        config
+            .pipeline
+            .get_or_insert_with(Vec::new)
+            .push(PipelineStep::Filter(self.step.clone()));
+    }
+}
+
+// One "Filter N: ON PK Fc 100 Hz Gain -3 dB Q 1.0" line, as written by
+// Equalizer APO, AutoEQ and REW.
+#[derive(Debug, Default)]
+pub(crate) struct FilterLine {
+    pub enabled: bool,
+    pub kind: String,
+    pub freq: Option<f64>,
+    pub gain: Option<f64>,
+    pub q: Option<f64>,
+    pub bandwidth: Option<f64>,
+    pub slope: Option<f64>,
+}
+
+fn number(token: Option<&str>, key: &str) -> Result<f64, String> {
+    let token = token.ok_or_else(|| format!("missing value for {}", key))?;
+    token
+        .parse::<f64>()
+        .map_err(|_| format!("invalid value '{}' for {}", token, key))
+}
+
+// Parses the part after "Filter N:".
+pub(crate) fn parse_filter_line(text: &str) -> Result<FilterLine, String> {
+    let mut tokens = text.split_whitespace().peekable();
+    let mut line = FilterLine {
+        enabled: match tokens.next() {
+            Some("ON") => true,
+            Some("OFF") => false,
+            other => return Err(format!("expected ON or OFF, found {:?}", other)),
+        },
+        kind: tokens.next().unwrap_or("None").to_string(),
+        ..Default::default()
+    };
+    while let Some(token) = tokens.next() {
+        match token {
+            "Fc" => line.freq = Some(number(tokens.next(), "Fc")?),
+            "Gain" => line.gain = Some(number(tokens.next(), "Gain")?),
+            "Q" => line.q = Some(number(tokens.next(), "Q")?),
+            "BW" => {
+                if tokens.peek() == Some(&"Oct") {
+                    tokens.next();
+                }
+                line.bandwidth = Some(number(tokens.next(), "BW")?);
+            }
+            "Hz" | "dB" => {}
+            // Shelf slopes are written directly after the type, e.g. "LS 12dB".
+            _ if token.ends_with("dB") && line.freq.is_none() => {
+                line.slope = Some(number(token.strip_suffix("dB"), "slope")?);
+            }
+            _ => return Err(format!("unexpected '{}'", token)),
+        }
+    }
+    Ok(line)
+}
+
+fn required(value: Option<f64>, key: &str, kind: &str) -> Result<f64, String> {
+    value.ok_or_else(|| format!("{} filter is missing {}", kind, key))
+}
+
+// Returns None for disabled filters and placeholders like "None".
+pub(crate) fn biquad_from_line(line: &FilterLine) -> Result<Option<BiquadParameters>, String> {
+    if !line.enabled || line.kind == "None" {
+        return Ok(None);
+    }
+    let kind = line.kind.as_str();
+    let freq = || required(line.freq, "Fc", kind);
+    let gain = || required(line.gain, "Gain", kind);
+    let shelf = || -> Result<ShelfSteepness, String> {
+        Ok(match (line.q, line.slope) {
+            (Some(q), _) => ShelfSteepness::Q {
+                freq: freq()?,
+                q,
+                gain: gain()?,
+            },
+            (None, slope) => ShelfSteepness::Slope {
+                freq: freq()?,
+                slope: slope.unwrap_or(12.0),
+                gain: gain()?,
+            },
+        })
+    };
+    let biquad = match kind {
+        "PK" | "PEQ" | "Modal" => match (line.q, line.bandwidth) {
+            (Some(q), _) => BiquadParameters::Peaking(PeakingWidth::Q {
+                freq: freq()?,
+                q,
+                gain: gain()?,
+            }),
+            (None, Some(bandwidth)) => BiquadParameters::Peaking(PeakingWidth::Bandwidth {
+                freq: freq()?,
+                bandwidth,
+                gain: gain()?,
+            }),
+            (None, None) => return Err(format!("{} filter is missing Q or BW", kind)),
+        },
+        "LS" | "LSC" | "LSQ" => BiquadParameters::Lowshelf(shelf()?),
+        "HS" | "HSC" | "HSQ" => BiquadParameters::Highshelf(shelf()?),
+        "LP" | "LPQ" => BiquadParameters::Lowpass {
+            freq: freq()?,
+            q: line.q.unwrap_or(std::f64::consts::FRAC_1_SQRT_2),
+        },
+        "HP" | "HPQ" => BiquadParameters::Highpass {
+            freq: freq()?,
+            q: line.q.unwrap_or(std::f64::consts::FRAC_1_SQRT_2),
+        },
+        "NO" => BiquadParameters::Notch(match line.bandwidth {
+            Some(bandwidth) if line.q.is_none() => NotchWidth::Bandwidth {
+                freq: freq()?,
+                bandwidth,
+            },
+            _ => NotchWidth::Q {
+                freq: freq()?,
+                q: line.q.unwrap_or(30.0),
+            },
+        }),
+        "AP" => BiquadParameters::Allpass(NotchWidth::Q {
+            freq: freq()?,
+            q: line.q.unwrap_or(std::f64::consts::FRAC_1_SQRT_2),
+        }),
+        _ => return Err(format!("unsupported filter type '{}'", kind)),
+    };
+    Ok(Some(biquad))
+}
@@ -0,0 +1,136 @@
+use super::{biquad_from_line, parse_filter_line, ParametricEq, ParseError};
+use crate::types::*;
+
+fn is_filter_line(line: &str) -> Option<&str> {
+    let rest = line.strip_prefix("Filter")?;
+    let (number, rest) = rest.split_once(':')?;
+    let number = number.trim();
+    if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) {
+        Some(rest)
+    } else {
+        None
+    }
+}
+
+/// Parse a REW "Filter Settings file" for one channel.
+/// Header lines are ignored, disabled and empty filter slots are skipped.
+/// Filters are named `<prefix>_1`, `<prefix>_2`, ... in file order.
+pub fn parse_rew(text: &str, prefix: &str, channel: usize) -> Result<ParametricEq, ParseError> {
+    let mut filters = Vec::new();
+    for (idx, raw) in text.lines().enumerate() {
+        let Some(rest) = is_filter_line(raw.trim()) else {
+            continue;
+        };
+        let error = |message: String| ParseError {
+            line: idx + 1,
+            message,
+        };
+        let filter_line = parse_filter_line(rest).map_err(error)?;
+        if let Some(biquad) = biquad_from_line(&filter_line).map_err(error)? {
+            filters.push((
+                format!("{}_{}", prefix, filters.len() + 1),
+                Filter::Biquad {
+                    description: None,
+                    parameters: biquad,
+                },
+            ));
+        }
+    }
+    Ok(ParametricEq::new(filters, Some(vec![channel])))
+}
+
+/// Import one REW filter file per channel into a configuration.
+/// The filters of channel N are named `rew_chN_1`, `rew_chN_2`, ...
+/// and each channel gets its own filter step.
+pub fn import_rew(config: &mut Configuration, files: &[(usize, &str)]) -> Result<(), ParseError> {
+    let mut imported = Vec::new();
+    for (channel, text) in files {
+        let eq =
+            parse_rew(text, &format!("rew_ch{}", channel), *channel).map_err(|e| ParseError {
+                line: e.line,
+                message: format!("channel {}: {}", channel, e.message),
+            })?;
+        imported.push(eq);
+    }
+    // Only modify the config once every file has been parsed.
+    for eq in imported {
+        eq.add_to(config);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REW: &str = "Filter Settings file
+
+Room EQ V5.20.13
+Dated: 14-Mar-2024 10:05:23
+
+Notes:
+
+Equaliser: Generic
+Left
+Filter  1: ON  PK       Fc   42.50 Hz  Gain  -9.50 dB  Q  5.000
+Filter  2: ON  LS       Fc   100.0 Hz  Gain   3.00 dB  Q  0.707
+Filter  3: ON  HS       Fc  8000.0 Hz  Gain  -2.00 dB
+Filter  4: ON  None
+Filter  5: OFF PK       Fc   63.60 Hz  Gain  -5.20 dB  Q  8.150
+";
+
+    #[test]
+    fn test_parse_rew() {
+        let eq = parse_rew(REW, "left", 0).unwrap();
+        assert_eq!(eq.step.channels, Some(vec![0]));
+        assert_eq!(eq.step.names, vec!["left_1", "left_2", "left_3"]);
+        assert_eq!(
+            eq.filters[0].1,
+            Filter::Biquad {
+                description: None,
+                parameters: BiquadParameters::Peaking(PeakingWidth::Q {
+                    freq: 42.5,
+                    q: 5.0,
+                    gain: -9.5
+                }),
+            }
+        );
+        assert!(matches!(
+            eq.filters[2].1,
+            Filter::Biquad {
+                parameters: BiquadParameters::Highshelf(ShelfSteepness::Slope { slope, .. }),
+                ..
+            } if slope == 12.0
+        ));
+    }
+
+    #[test]
+    fn test_import_rew_into_config() {
+        let mut config = Configuration::from_yaml_string(
+            r#"---
+devices:
+  samplerate: 48000
+  chunksize: 1024
+  capture:
+    type: Stdin
+    channels: 2
+    format: S16_LE
+  playback:
+    type: Stdout
+    channels: 2
+    format: S16_LE
+"#,
+        )
+        .unwrap();
+        import_rew(&mut config, &[(0, REW), (1, REW)]).unwrap();
+        assert_eq!(config.filters.as_ref().unwrap().len(), 6);
+        assert!(config.filters.as_ref().unwrap().contains_key("rew_ch1_3"));
+        assert_eq!(config.pipeline.as_ref().unwrap().len(), 2);
+        assert_eq!(config.validate(), Ok(()));
+
+        let broken = "Filter  1: ON  PK Fc 42.50 Hz Q 5.0\n";
+        let err = import_rew(&mut config, &[(1, broken)]).unwrap_err();
+        assert_eq!(err.message, "channel 1: PK filter is missing Gain");
+        assert_eq!(config.pipeline.as_ref().unwrap().len(), 2);
+    }
+}
@@ -1,28 +1,53 @@
+pub mod builder;
+pub mod channels;
+pub mod crossover;
+pub mod diff;
+pub mod dsp;
+pub mod edit;
+pub mod engine;
+pub mod error;
+pub mod graph;
+pub mod interop;
+pub mod matrix;
+pub mod migrate;
+pub mod optimize;
+pub mod presets;
+pub mod schema;
+pub mod statefile;
 pub mod types;
+pub mod validation;
+pub mod websocket;
+pub use builder::ConfigurationBuilder;
+pub use channels::{ChannelFlow, StepChannels, StepKind};
+pub use error::{ConfigError, ConfigErrorKind, VariantMismatch};
+pub use indexmap::IndexMap;
+pub use statefile::{StateFile, StateFileError};
 pub use types::*;
+pub use validation::{ValidationError, ValidationErrorKind};
 
 impl Configuration {
     pub fn to_yaml_string(&self) -> Result<String, serde_yaml::Error> {
         serde_yaml::to_string(self)
     }
 
-    pub fn from_yaml_string(yaml: &str) -> Result<Self, serde_yaml::Error> {
-        serde_yaml::from_str(yaml)
+    pub fn from_yaml_string(yaml: &str) -> Result<Self, ConfigError> {
+        serde_yaml::from_str(yaml).map_err(|error| ConfigError::from_yaml(yaml, error))
     }
 
     pub fn to_yaml_writer<W: std::io::Write>(&self, writer: W) -> Result<(), serde_yaml::Error> {
         serde_yaml::to_writer(writer, self)
     }
 
-    pub fn from_yaml_reader<R: std::io::Read>(reader: R) -> Result<Self, serde_yaml::Error> {
-        serde_yaml::from_reader(reader)
+    pub fn from_yaml_reader<R: std::io::Read>(mut reader: R) -> Result<Self, ConfigError> {
+        let mut yaml = String::new();
+        reader.read_to_string(&mut yaml)?;
+        Configuration::from_yaml_string(&yaml)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
 
     #[test]
     fn test_roundtrip_simpleconfig() {
@@ -175,7 +200,7 @@ pipeline:
                 worker_threads: None,
             },
             mixers: None,
-            filters: Some(HashMap::from([(
+            filters: Some(IndexMap::from([(
                 "my_gain".to_string(),
                 Filter::Gain {
                     description: Some("Volume adjustment".to_string()),
@@ -375,4 +400,114 @@ pipeline:
             _ => panic!("Expected RACE processor"),
         }
     }
+
+    #[test]
+    fn test_roundtrip_preserves_map_order() {
+        let yaml = r#"---
+devices:
+  samplerate: 48000
+  chunksize: 1024
+  capture:
+    type: Stdin
+    channels: 2
+    format: S16_LE
+  playback:
+    type: Stdout
+    channels: 2
+    format: S16_LE
+mixers:
+  zmix:
+    channels:
+      in: 2
+      out: 2
+    mapping: []
+  amix:
+    channels:
+      in: 2
+      out: 2
+    mapping: []
+filters:
+  zeta:
+    type: Gain
+    parameters:
+      gain: -1
+  alpha:
+    type: Gain
+    parameters:
+      gain: -2
+  mid:
+    type: Gain
+    parameters:
+      gain: -3
+  beta:
+    type: Gain
+    parameters:
+      gain: -4
+processors:
+  second:
+    type: NoiseGate
+    parameters:
+      channels: 2
+      attack: 0.1
+      release: 1.0
+      threshold: -50
+      attenuation: 30
+  first:
+    type: NoiseGate
+    parameters:
+      channels: 2
+      attack: 0.1
+      release: 1.0
+      threshold: -50
+      attenuation: 30
+"#;
+        let config = Configuration::from_yaml_string(yaml).expect("Failed to parse");
+        let keys = |config: &Configuration| {
+            (
+                config
+                    .mixers
+                    .as_ref()
+                    .unwrap()
+                    .keys()
+                    .cloned()
+                    .collect::<Vec<_>>(),
+                config
+                    .filters
+                    .as_ref()
+                    .unwrap()
+                    .keys()
+                    .cloned()
+                    .collect::<Vec<_>>(),
+                config
+                    .processors
+                    .as_ref()
+                    .unwrap()
+                    .keys()
+                    .cloned()
+                    .collect::<Vec<_>>(),
+            )
+        };
+        let expected = (
+            vec!["zmix".to_string(), "amix".to_string()],
+            vec![
+                "zeta".to_string(),
+                "alpha".to_string(),
+                "mid".to_string(),
+                "beta".to_string(),
+            ],
+            vec!["second".to_string(), "first".to_string()],
+        );
+        assert_eq!(keys(&config), expected);
+
+        let yaml_out = config.to_yaml_string().expect("Failed to serialize");
+        let positions: Vec<usize> = ["zmix:", "amix:", "zeta:", "alpha:", "mid:", "beta:"]
+            .iter()
+            .map(|key| yaml_out.find(key).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let config2 = Configuration::from_yaml_string(&yaml_out).expect("Failed to re-parse");
+        assert_eq!(keys(&config2), expected);
+        assert_eq!(config2.to_yaml_string().unwrap(), yaml_out);
+    }
 }
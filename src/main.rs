@@ -0,0 +1,412 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+
+use camilladsp_config::{schema, Configuration};
+
+const USAGE: &str = "\
+Usage: camilladsp-config <command> [arguments]
+
+Commands:
+  validate <file>...                  Check that the files are valid configs
+  fmt [--check | --write] <file>      Print the file in canonical form
+  convert --to json|yaml <file>       Print the file as json or yaml
+  diff <old> <new>                    Show what changes between two configs
+  schema [--openapi]                  Print the JSON Schema of a config file
+  graph [--mermaid] <file>            Print the signal flow as Graphviz DOT or Mermaid
+
+A file named - is read from stdin. The exit status is 0 on success,
+1 when a check fails or the configs differ, and 2 for usage errors.";
+
+#[derive(Debug)]
+enum CliError {
+    Usage(String),
+    Failed(String),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(message) => write!(f, "{}\n\n{}", message, USAGE),
+            CliError::Failed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(error: io::Error) -> Self {
+        CliError::Failed(error.to_string())
+    }
+}
+
+fn usage(message: impl Into<String>) -> CliError {
+    CliError::Usage(message.into())
+}
+
+fn read_text(path: &str) -> Result<String, CliError> {
+    if path == "-" {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text)?;
+        Ok(text)
+    } else {
+        fs::read_to_string(path).map_err(|e| CliError::Failed(format!("{}: {}", path, e)))
+    }
+}
+
+/// Parse a config file, json is accepted as well since it is a subset of yaml.
+fn parse(path: &str, text: &str) -> Result<Configuration, CliError> {
+    Configuration::from_yaml_string(text).map_err(|e| CliError::Failed(format!("{}: {}", path, e)))
+}
+
+fn read_config(path: &str) -> Result<Configuration, CliError> {
+    parse(path, &read_text(path)?)
+}
+
+fn to_yaml(config: &Configuration) -> Result<String, CliError> {
+    config
+        .to_yaml_string()
+        .map_err(|e| CliError::Failed(e.to_string()))
+}
+
+/// Options given to a command, with their value if they take one.
+type Flags<'a> = Vec<(&'a str, Option<&'a str>)>;
+
+/// Split the arguments of a command into flags and positional arguments.
+fn split_args<'a>(
+    args: &'a [String],
+    flags_with_value: &[&str],
+) -> Result<(Flags<'a>, Vec<&'a str>), CliError> {
+    let mut flags = Vec::new();
+    let mut positional = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg.starts_with("--") {
+            if let Some((flag, value)) = arg.split_once('=') {
+                flags.push((flag, Some(value)));
+            } else if flags_with_value.contains(&arg.as_str()) {
+                let value = args
+                    .next()
+                    .ok_or_else(|| usage(format!("{} needs a value", arg)))?;
+                flags.push((arg.as_str(), Some(value.as_str())));
+            } else {
+                flags.push((arg.as_str(), None));
+            }
+        } else {
+            positional.push(arg.as_str());
+        }
+    }
+    Ok((flags, positional))
+}
+
+fn one_file<'a>(positional: &[&'a str]) -> Result<&'a str, CliError> {
+    match positional {
+        [path] => Ok(path),
+        [] => Err(usage("missing file")),
+        _ => Err(usage("expected a single file")),
+    }
+}
+
+fn validate(args: &[String], out: &mut dyn Write) -> Result<bool, CliError> {
+    let (flags, files) = split_args(args, &[])?;
+    if let Some((flag, _)) = flags.first() {
+        return Err(usage(format!("unknown option {}", flag)));
+    }
+    if files.is_empty() {
+        return Err(usage("missing file"));
+    }
+    let mut valid = true;
+    for path in files {
+        let config = match read_config(path) {
+            Ok(config) => config,
+            Err(error) => {
+                writeln!(out, "{}", error)?;
+                valid = false;
+                continue;
+            }
+        };
+        match config.validate() {
+            Ok(()) => writeln!(out, "{}: ok", path)?,
+            Err(errors) => {
+                for error in errors {
+                    writeln!(out, "{}: {}", path, error)?;
+                }
+                valid = false;
+            }
+        }
+    }
+    Ok(valid)
+}
+
+fn format(args: &[String], out: &mut dyn Write) -> Result<bool, CliError> {
+    let (flags, files) = split_args(args, &[])?;
+    let path = one_file(&files)?;
+    let (mut check, mut write) = (false, false);
+    for (flag, _) in flags {
+        match flag {
+            "--check" => check = true,
+            "--write" => write = true,
+            _ => return Err(usage(format!("unknown option {}", flag))),
+        }
+    }
+    if check && write {
+        return Err(usage("--check and --write can not be combined"));
+    }
+    if write && path == "-" {
+        return Err(usage("--write needs a file"));
+    }
+    let text = read_text(path)?;
+    let formatted = to_yaml(&parse(path, &text)?)?;
+    if check {
+        if formatted == text {
+            return Ok(true);
+        }
+        writeln!(out, "{}: not formatted", path)?;
+        return Ok(false);
+    }
+    if write {
+        if formatted != text {
+            fs::write(path, formatted).map_err(|e| CliError::Failed(format!("{}: {}", path, e)))?;
+        }
+    } else {
+        out.write_all(formatted.as_bytes())?;
+    }
+    Ok(true)
+}
+
+fn convert(args: &[String], out: &mut dyn Write) -> Result<bool, CliError> {
+    let (flags, files) = split_args(args, &["--to"])?;
+    let path = one_file(&files)?;
+    let mut target = None;
+    for (flag, value) in flags {
+        match (flag, value) {
+            ("--to", Some(value)) => target = Some(value),
+            _ => return Err(usage(format!("unknown option {}", flag))),
+        }
+    }
+    let config = read_config(path)?;
+    let text = match target {
+        Some("yaml") => to_yaml(&config)?,
+        Some("json") => {
+            let mut json = serde_json::to_string_pretty(&config)
+                .map_err(|e| CliError::Failed(e.to_string()))?;
+            json.push('\n');
+            json
+        }
+        Some(other) => return Err(usage(format!("unknown format {}", other))),
+        None => return Err(usage("missing --to json|yaml")),
+    };
+    out.write_all(text.as_bytes())?;
+    Ok(true)
+}
+
+fn diff(args: &[String], out: &mut dyn Write) -> Result<bool, CliError> {
+    let (flags, files) = split_args(args, &[])?;
+    if let Some((flag, _)) = flags.first() {
+        return Err(usage(format!("unknown option {}", flag)));
+    }
+    let [old, new] = files[..] else {
+        return Err(usage("expected two files"));
+    };
+    let diff = read_config(old)?.diff(&read_config(new)?);
+    write!(out, "{}", diff)?;
+    Ok(diff.is_empty())
+}
+
+fn print_schema(args: &[String], out: &mut dyn Write) -> Result<bool, CliError> {
+    let (flags, files) = split_args(args, &[])?;
+    if !files.is_empty() {
+        return Err(usage("schema takes no files"));
+    }
+    let mut openapi = false;
+    for (flag, _) in flags {
+        match flag {
+            "--openapi" => openapi = true,
+            _ => return Err(usage(format!("unknown option {}", flag))),
+        }
+    }
+    let text = if openapi {
+        schema::openapi().to_pretty_json()
+    } else {
+        serde_json::to_string_pretty(&schema::configuration_json_schema())
+    };
+    let text = text.map_err(|e| CliError::Failed(e.to_string()))?;
+    writeln!(out, "{}", text)?;
+    Ok(true)
+}
+
+fn graph(args: &[String], out: &mut dyn Write) -> Result<bool, CliError> {
+    let (flags, files) = split_args(args, &[])?;
+    let path = one_file(&files)?;
+    let mut mermaid = false;
+    for (flag, _) in flags {
+        match flag {
+            "--mermaid" => mermaid = true,
+            _ => return Err(usage(format!("unknown option {}", flag))),
+        }
+    }
+    let config = read_config(path)?;
+    let text = if mermaid {
+        config.to_mermaid()
+    } else {
+        config.to_dot()
+    };
+    out.write_all(text.as_bytes())?;
+    Ok(true)
+}
+
+/// Run a command, returns false when a check fails or the configs differ.
+fn run(args: &[String], out: &mut dyn Write) -> Result<bool, CliError> {
+    let Some((command, args)) = args.split_first() else {
+        return Err(usage("missing command"));
+    };
+    match command.as_str() {
+        "validate" => validate(args, out),
+        "fmt" => format(args, out),
+        "convert" => convert(args, out),
+        "diff" => diff(args, out),
+        "schema" => print_schema(args, out),
+        "graph" => graph(args, out),
+        "help" | "--help" | "-h" => {
+            writeln!(out, "{}", USAGE)?;
+            Ok(true)
+        }
+        _ => Err(usage(format!("unknown command {}", command))),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let stdout = io::stdout();
+    match run(&args, &mut stdout.lock()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(error) => {
+            eprintln!("{}", error);
+            match error {
+                CliError::Usage(_) => ExitCode::from(2),
+                CliError::Failed(_) => ExitCode::from(1),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"---
+devices:
+  samplerate: 48000
+  chunksize: 1024
+  capture:
+    type: Stdin
+    channels: 2
+    format: S16_LE
+  playback:
+    type: Stdout
+    channels: 2
+    format: S16_LE
+filters:
+  vol:
+    type: Gain
+    parameters:
+      gain: -6
+pipeline:
+  - type: Filter
+    channels: [0, 1]
+    names: [vol]
+"#;
+
+    /// A directory for the files of one test, removed when the test ends.
+    struct TempDir(std::path::PathBuf);
+
+    impl TempDir {
+        fn new(test: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "camilladsp-config-cli-{}-{}",
+                std::process::id(),
+                test
+            ));
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+
+        fn file(&self, name: &str, text: &str) -> String {
+            let path = self.0.join(name);
+            fs::write(&path, text).unwrap();
+            path.to_string_lossy().into_owned()
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn call(args: &[&str]) -> (Result<bool, CliError>, String) {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        let mut out = Vec::new();
+        let result = run(&args, &mut out);
+        (result, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_validate_and_diff() {
+        let dir = TempDir::new("validate");
+        let good = dir.file("good.yml", CONFIG);
+        let bad = dir.file("bad.yml", &CONFIG.replace("names: [vol]", "names: [eq]"));
+
+        let (result, out) = call(&["validate", &good]);
+        assert!(result.unwrap());
+        assert_eq!(out, format!("{}: ok\n", good));
+        let (result, out) = call(&["validate", &good, &bad]);
+        assert!(!result.unwrap());
+        assert!(out.contains(&format!("{}: pipeline[0].names[0]", bad)));
+
+        let (result, out) = call(&["diff", &good, &good]);
+        assert!(result.unwrap());
+        assert_eq!(out, "no changes\n");
+        let (result, out) = call(&["diff", &good, &bad]);
+        assert!(!result.unwrap());
+        assert!(out.starts_with("~ pipeline[0].names: [vol] -> [eq]\n"));
+
+        assert!(matches!(call(&["diff", &good]).0, Err(CliError::Usage(_))));
+        assert!(matches!(call(&["lint"]).0, Err(CliError::Usage(_))));
+    }
+
+    #[test]
+    fn test_fmt_and_convert() {
+        let dir = TempDir::new("fmt");
+        let path = dir.file("fmt.yml", CONFIG);
+        let (result, _) = call(&["fmt", "--check", &path]);
+        assert!(!result.unwrap());
+        let (result, _) = call(&["fmt", "--write", &path]);
+        assert!(result.unwrap());
+        let (result, out) = call(&["fmt", "--check", &path]);
+        assert!(result.unwrap());
+        assert_eq!(out, "");
+
+        let (result, json) = call(&["convert", "--to", "json", &path]);
+        assert!(result.unwrap());
+        let json_path = dir.file("fmt.json", &json);
+        let (result, yaml) = call(&["convert", "--to=yaml", &json_path]);
+        assert!(result.unwrap());
+        assert_eq!(yaml, fs::read_to_string(&path).unwrap());
+        assert!(matches!(
+            call(&["convert", "--to", "toml", &path]).0,
+            Err(CliError::Usage(_))
+        ));
+
+        let (result, schema) = call(&["schema"]);
+        assert!(result.unwrap());
+        assert!(schema.contains("\"$defs\""));
+
+        let (result, dot) = call(&["graph", &path]);
+        assert!(result.unwrap());
+        assert!(dot.contains("    p0_c1_0 -> playback_1;\n"));
+        let (_, mermaid) = call(&["graph", "--mermaid", &path]);
+        assert!(mermaid.starts_with("flowchart LR\n"));
+    }
+}
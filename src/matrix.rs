@@ -0,0 +1,319 @@
+use std::fmt;
+
+use crate::types::*;
+
+// Gains closer than this are considered equal.
+const TOLERANCE: f64 = 1e-9;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MatrixError {
+    /// The rows of a matrix do not all have the same length.
+    RaggedRows,
+    /// The outputs of the first mixer do not match the inputs of the second.
+    ChannelMismatch { outputs: usize, inputs: usize },
+}
+
+impl fmt::Display for MatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatrixError::RaggedRows => write!(f, "all rows must have the same length"),
+            MatrixError::ChannelMismatch { outputs, inputs } => write!(
+                f,
+                "first mixer has {} output channels but the second takes {} input channels",
+                outputs, inputs
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MatrixError {}
+
+/// Dense linear gains of a mixer, one row per output channel
+/// and one column per input channel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MixerMatrix {
+    inputs: usize,
+    rows: Vec<Vec<f64>>,
+}
+
+impl MixerMatrix {
+    /// A matrix that routes nothing.
+    pub fn new(inputs: usize, outputs: usize) -> Self {
+        MixerMatrix {
+            inputs,
+            rows: vec![vec![0.0; inputs]; outputs],
+        }
+    }
+
+    pub fn identity(channels: usize) -> Self {
+        let mut matrix = MixerMatrix::new(channels, channels);
+        for channel in 0..channels {
+            matrix.rows[channel][channel] = 1.0;
+        }
+        matrix
+    }
+
+    pub fn from_rows(inputs: usize, rows: Vec<Vec<f64>>) -> Result<Self, MatrixError> {
+        if rows.iter().any(|row| row.len() != inputs) {
+            return Err(MatrixError::RaggedRows);
+        }
+        Ok(MixerMatrix { inputs, rows })
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    pub fn outputs(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn rows(&self) -> &[Vec<f64>] {
+        &self.rows
+    }
+
+    /// Linear gain from input channel `input` to output channel `output`.
+    pub fn gain(&self, output: usize, input: usize) -> f64 {
+        self.rows[output][input]
+    }
+
+    pub fn set_gain(&mut self, output: usize, input: usize, gain: f64) {
+        self.rows[output][input] = gain;
+    }
+
+    /// The matrix of applying `self` and then `next`.
+    pub fn then(&self, next: &MixerMatrix) -> Result<MixerMatrix, MatrixError> {
+        if self.outputs() != next.inputs {
+            return Err(MatrixError::ChannelMismatch {
+                outputs: self.outputs(),
+                inputs: next.inputs,
+            });
+        }
+        let rows = next
+            .rows
+            .iter()
+            .map(|next_row| {
+                (0..self.inputs)
+                    .map(|input| {
+                        next_row
+                            .iter()
+                            .zip(&self.rows)
+                            .map(|(gain, row)| gain * row[input])
+                            .sum()
+                    })
+                    .collect()
+            })
+            .collect();
+        Ok(MixerMatrix {
+            inputs: self.inputs,
+            rows,
+        })
+    }
+
+    /// True when every output is a copy of the input with the same index.
+    pub fn is_identity(&self) -> bool {
+        self.inputs == self.outputs()
+            && self.rows.iter().enumerate().all(|(output, row)| {
+                row.iter().enumerate().all(|(input, gain)| {
+                    let expected = if input == output { 1.0 } else { 0.0 };
+                    (gain - expected).abs() < TOLERANCE
+                })
+            })
+    }
+
+    /// Build a mixer with one source per non-zero gain, in dB with `inverted` for negative gains.
+    /// Outputs without sources get no mapping and stay silent.
+    pub fn to_mixer(&self) -> Mixer {
+        let mapping = self
+            .rows
+            .iter()
+            .enumerate()
+            .filter_map(|(dest, row)| {
+                let sources: Vec<MixerSource> = row
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, gain)| gain.abs() >= TOLERANCE)
+                    .map(|(channel, gain)| MixerSource {
+                        channel,
+                        gain: Some(20.0 * gain.abs().log10()),
+                        inverted: (*gain < 0.0).then_some(true),
+                        mute: None,
+                        scale: Some(GainScale::Decibel),
+                    })
+                    .collect();
+                (!sources.is_empty()).then_some(MixerMapping {
+                    dest,
+                    sources,
+                    mute: None,
+                })
+            })
+            .collect();
+        Mixer {
+            description: None,
+            channels: MixerChannels {
+                r#in: self.inputs,
+                out: self.outputs(),
+            },
+            mapping,
+            labels: None,
+        }
+    }
+}
+
+impl fmt::Display for MixerMatrix {
+    /// A routing grid with linear gains, `-` where nothing is routed.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:>8}", "")?;
+        for input in 0..self.inputs {
+            write!(f, "{:>8}", format!("in {}", input))?;
+        }
+        writeln!(f)?;
+        for (output, row) in self.rows.iter().enumerate() {
+            write!(f, "{:>8}", format!("out {}", output))?;
+            for gain in row {
+                if gain.abs() < TOLERANCE {
+                    write!(f, "{:>8}", "-")?;
+                } else {
+                    write!(f, "{:>8.3}", gain)?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Mixer {
+    /// The linear gains of the mixer. Gains of sources mapped to the same output
+    /// add up, muted mappings and sources give zero. Channels out of range are skipped,
+    /// `validate` reports them.
+    pub fn matrix(&self) -> MixerMatrix {
+        let mut matrix = MixerMatrix::new(self.channels.r#in, self.channels.out);
+        for mapping in &self.mapping {
+            if mapping.mute.unwrap_or(false) || mapping.dest >= self.channels.out {
+                continue;
+            }
+            for source in &mapping.sources {
+                if source.channel < self.channels.r#in {
+                    matrix.rows[mapping.dest][source.channel] += source.linear_gain();
+                }
+            }
+        }
+        matrix
+    }
+
+    /// A single mixer doing the same as this mixer followed by `next`.
+    /// The labels are taken from `next`.
+    pub fn compose(&self, next: &Mixer) -> Result<Mixer, MatrixError> {
+        let mut mixer = self.matrix().then(&next.matrix())?.to_mixer();
+        mixer.labels = next.labels.clone();
+        Ok(mixer)
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.matrix().is_identity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mixer(yaml: &str) -> Mixer {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_matrix_roundtrip() {
+        let mixer = mixer(
+            r#"
+channels:
+  in: 2
+  out: 3
+mapping:
+  - dest: 0
+    sources:
+      - channel: 0
+        gain: -6
+      - channel: 1
+        gain: 0.5
+        scale: linear
+        inverted: true
+  - dest: 1
+    mute: true
+    sources:
+      - channel: 1
+  - dest: 2
+    sources:
+      - channel: 1
+      - channel: 1
+        mute: true
+"#,
+        );
+        let matrix = mixer.matrix();
+        assert_eq!((matrix.inputs(), matrix.outputs()), (2, 3));
+        assert!((matrix.gain(0, 0) - 0.501187).abs() < 1e-6);
+        assert_eq!(matrix.gain(0, 1), -0.5);
+        assert_eq!(matrix.rows()[1], vec![0.0, 0.0]);
+        assert_eq!(matrix.rows()[2], vec![0.0, 1.0]);
+
+        let rebuilt = matrix.to_mixer();
+        assert_eq!(rebuilt.mapping.len(), 2);
+        assert_eq!(rebuilt.mapping[0].sources[1].inverted, Some(true));
+        let again = rebuilt.matrix();
+        for (a, b) in again
+            .rows()
+            .iter()
+            .flatten()
+            .zip(matrix.rows().iter().flatten())
+        {
+            assert!((a - b).abs() < 1e-12);
+        }
+        assert_eq!(
+            matrix.to_string().lines().nth(3).unwrap(),
+            "   out 2       -   1.000"
+        );
+    }
+
+    #[test]
+    fn test_compose_and_identity() {
+        let swap = mixer(
+            r#"
+channels:
+  in: 2
+  out: 2
+mapping:
+  - dest: 0
+    sources:
+      - channel: 1
+  - dest: 1
+    sources:
+      - channel: 0
+"#,
+        );
+        assert!(!swap.is_identity());
+        assert!(swap.compose(&swap).unwrap().is_identity());
+        assert!(MixerMatrix::identity(3).is_identity());
+
+        let mono = MixerMatrix::from_rows(2, vec![vec![0.5, 0.5]])
+            .unwrap()
+            .to_mixer();
+        let both = swap.compose(&mono).unwrap();
+        assert_eq!(both.channels, MixerChannels { r#in: 2, out: 1 });
+        let gains = both.matrix();
+        assert!((gains.gain(0, 0) - 0.5).abs() < 1e-12);
+        assert!((gains.gain(0, 1) - 0.5).abs() < 1e-12);
+
+        assert_eq!(
+            mono.compose(&swap),
+            Err(MatrixError::ChannelMismatch {
+                outputs: 1,
+                inputs: 2
+            })
+        );
+        assert_eq!(
+            MixerMatrix::from_rows(2, vec![vec![1.0]]),
+            Err(MatrixError::RaggedRows)
+        );
+    }
+}
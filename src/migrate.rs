@@ -0,0 +1,450 @@
+use std::fmt;
+
+use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
+
+use crate::error::ConfigError;
+use crate::types::Configuration;
+
+/// One rewrite made while upgrading an old config.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MigrationChange {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for MigrationChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+#[derive(Debug)]
+pub enum MigrationError {
+    /// The input is not valid YAML.
+    Parse(serde_yaml::Error),
+    /// The rewritten config is still not a valid current config.
+    Invalid {
+        error: Box<ConfigError>,
+        changes: Vec<MigrationChange>,
+    },
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::Parse(error) => write!(f, "could not parse config: {}", error),
+            MigrationError::Invalid { error, .. } => {
+                write!(f, "migrated config is not valid: {}", error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+/// A config upgraded to the current format, with the list of changes that were made.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Migration {
+    pub config: Configuration,
+    pub changes: Vec<MigrationChange>,
+}
+
+/// Read a CamillaDSP v1, v2 or current config and upgrade it to the current types.
+pub fn migrate_yaml(yaml: &str) -> Result<Migration, MigrationError> {
+    let mut value: Value = serde_yaml::from_str(yaml).map_err(MigrationError::Parse)?;
+    let changes = migrate_value(&mut value);
+    match Configuration::deserialize(&value) {
+        Ok(config) => Ok(Migration { config, changes }),
+        Err(error) => Err(MigrationError::Invalid {
+            error: Box::new(ConfigError::from_value(&value, error)),
+            changes,
+        }),
+    }
+}
+
+/// Rewrite an old config in place and return the changes made.
+/// Values that are already in the current format are left untouched,
+/// so migrating twice is the same as migrating once.
+pub fn migrate_value(config: &mut Value) -> Vec<MigrationChange> {
+    let mut changes = Changes(Vec::new());
+    if let Some(devices) = get_mapping(config, "devices") {
+        migrate_resampler(devices, &mut changes);
+        for (key, capture) in [("capture", true), ("playback", false)] {
+            if let Some(device) = devices.get_mut(key).and_then(Value::as_mapping_mut) {
+                migrate_device(device, capture, &format!("devices.{}", key), &mut changes);
+            }
+        }
+    }
+    let mut removed = Vec::new();
+    if let Some(filters) = get_mapping(config, "filters") {
+        let names: Vec<Value> = filters.keys().cloned().collect();
+        for name in names {
+            let path = format!("filters.{}", name.as_str().unwrap_or_default());
+            let filter = filters.get_mut(&name).unwrap();
+            if is_main_volume(filter) {
+                filters.remove(&name);
+                changes.push(
+                    &path,
+                    "removed Volume filter for the main fader, main volume is now applied by default",
+                );
+                removed.push(name);
+            } else if let Some(filter) = filter.as_mapping_mut() {
+                migrate_filter(filter, &path, &mut changes);
+            }
+        }
+    }
+    if let Some(pipeline) = config
+        .as_mapping_mut()
+        .and_then(|map| map.get_mut("pipeline"))
+        .and_then(Value::as_sequence_mut)
+    {
+        migrate_pipeline(pipeline, &removed, &mut changes);
+    }
+    changes.0
+}
+
+struct Changes(Vec<MigrationChange>);
+
+impl Changes {
+    fn push(&mut self, path: &str, message: impl Into<String>) {
+        self.0.push(MigrationChange {
+            path: path.to_string(),
+            message: message.into(),
+        });
+    }
+}
+
+fn get_mapping<'a>(value: &'a mut Value, key: &str) -> Option<&'a mut Mapping> {
+    value.as_mapping_mut()?.get_mut(key)?.as_mapping_mut()
+}
+
+fn get_str<'a>(map: &'a Mapping, key: &str) -> Option<&'a str> {
+    map.get(key).and_then(Value::as_str)
+}
+
+fn rename_type(map: &mut Mapping, from: &str, to: &str, path: &str, changes: &mut Changes) {
+    if get_str(map, "type") == Some(from) {
+        map.insert("type".into(), to.into());
+        changes.push(path, format!("renamed type {} to {}", from, to));
+    }
+}
+
+// --- Devices ---
+
+fn migrate_resampler(devices: &mut Mapping, changes: &mut Changes) {
+    let enabled = devices.remove("enable_resampling");
+    let old = devices.remove("resampler_type");
+    if enabled.is_none() && old.is_none() {
+        return;
+    }
+    if enabled.and_then(|e| e.as_bool()) != Some(true) {
+        changes.push(
+            "devices.enable_resampling",
+            "removed, resampling was disabled",
+        );
+        return;
+    }
+    let resampler = match old {
+        Some(Value::String(name)) => match name.as_str() {
+            "Synchronous" => Some(("Synchronous", None)),
+            "FastAsync" => Some(("AsyncSinc", Some("Fast"))),
+            "BalancedAsync" => Some(("AsyncSinc", Some("Balanced"))),
+            "AccurateAsync" => Some(("AsyncSinc", Some("Accurate"))),
+            _ => None,
+        }
+        .map(|(kind, profile)| {
+            let mut map = Mapping::new();
+            map.insert("type".into(), kind.into());
+            if let Some(profile) = profile {
+                map.insert("profile".into(), profile.into());
+            }
+            map
+        }),
+        Some(Value::Mapping(old)) => old
+            .get("FreeAsync")
+            .and_then(Value::as_mapping)
+            .map(|free| {
+                let mut map = Mapping::new();
+                map.insert("type".into(), "AsyncSinc".into());
+                for (key, value) in free {
+                    let key = match key.as_str() {
+                        Some("oversampling_ratio") => "oversampling_factor".into(),
+                        _ => key.clone(),
+                    };
+                    map.insert(key, value.clone());
+                }
+                map
+            }),
+        _ => None,
+    };
+    match resampler {
+        Some(resampler) => {
+            devices.insert("resampler".into(), Value::Mapping(resampler));
+            changes.push(
+                "devices.resampler",
+                "replaced enable_resampling and resampler_type",
+            );
+        }
+        None => changes.push(
+            "devices.resampler_type",
+            "removed unknown resampler type, resampling is disabled",
+        ),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum FormatKind {
+    Binary,
+    File,
+    Alsa,
+    Short,
+}
+
+fn migrate_format(name: &str, kind: FormatKind) -> Option<&'static str> {
+    let format = match (name, kind) {
+        ("S16LE", FormatKind::Short) => "S16",
+        ("S24LE" | "S24LE3", FormatKind::Short) => "S24",
+        ("S32LE", FormatKind::Short) => "S32",
+        ("FLOAT32LE", FormatKind::Short) => "F32",
+        ("S16LE", _) => "S16_LE",
+        ("S24LE", FormatKind::Alsa) => "S24_4_LE",
+        ("S24LE", _) => "S24_4_RJ_LE",
+        ("S24LE3", _) => "S24_3_LE",
+        ("S32LE", _) => "S32_LE",
+        ("FLOAT32LE", _) => "F32_LE",
+        ("FLOAT64LE", _) => "F64_LE",
+        _ => return None,
+    };
+    Some(format)
+}
+
+fn rename_format(map: &mut Mapping, kind: FormatKind, path: &str, changes: &mut Changes) {
+    let Some(old) = get_str(map, "format") else {
+        return;
+    };
+    if let Some(new) = migrate_format(old, kind) {
+        changes.push(path, format!("renamed format {} to {}", old, new));
+        map.insert("format".into(), new.into());
+    }
+}
+
+fn migrate_device(device: &mut Mapping, capture: bool, path: &str, changes: &mut Changes) {
+    if capture {
+        rename_type(device, "File", "RawFile", path, changes);
+    }
+    if device.remove("change_format").is_some() {
+        changes.push(path, "removed change_format, it is no longer supported");
+    }
+    let kind = match get_str(device, "type").map(str::to_lowercase).as_deref() {
+        Some("alsa" | "asio") => FormatKind::Alsa,
+        Some("coreaudio" | "wasapi") => FormatKind::Short,
+        _ => FormatKind::Binary,
+    };
+    rename_format(device, kind, path, changes);
+}
+
+// --- Filters ---
+
+fn is_main_volume(filter: &Value) -> bool {
+    let Some(filter) = filter.as_mapping() else {
+        return false;
+    };
+    if get_str(filter, "type") != Some("Volume") {
+        return false;
+    }
+    let fader = filter
+        .get("parameters")
+        .and_then(Value::as_mapping)
+        .and_then(|p| get_str(p, "fader"));
+    matches!(fader, None | Some("Main"))
+}
+
+fn migrate_filter(filter: &mut Mapping, path: &str, changes: &mut Changes) {
+    let kind = get_str(filter, "type").map(str::to_string);
+    let Some(parameters) = filter.get_mut("parameters").and_then(Value::as_mapping_mut) else {
+        return;
+    };
+    match kind.as_deref() {
+        Some("Conv") => {
+            if !parameters.contains_key("type") {
+                let inferred = if parameters.contains_key("values") {
+                    "Values"
+                } else if get_str(parameters, "filename")
+                    .is_some_and(|f| f.to_lowercase().ends_with(".wav"))
+                {
+                    "Wav"
+                } else {
+                    "Raw"
+                };
+                parameters.insert("type".into(), inferred.into());
+                changes.push(path, format!("added missing Conv type {}", inferred));
+            }
+            rename_type(parameters, "File", "Raw", path, changes);
+            if get_str(parameters, "type") == Some("Raw") {
+                rename_format(parameters, FormatKind::File, path, changes);
+            }
+        }
+        Some("Dither") => {
+            rename_type(parameters, "Simple", "Highpass", path, changes);
+            rename_type(parameters, "Uniform", "Flat", path, changes);
+        }
+        _ => {}
+    }
+    if kind.as_deref() == Some("Loudness") && parameters.remove("ramp_time").is_some() {
+        changes.push(path, "removed ramp_time, use devices.volume_ramp_time");
+    }
+}
+
+// --- Pipeline ---
+
+fn migrate_pipeline(pipeline: &mut Vec<Value>, removed: &[Value], changes: &mut Changes) {
+    for (idx, step) in pipeline.iter_mut().enumerate() {
+        let Some(step) = step.as_mapping_mut() else {
+            continue;
+        };
+        let path = format!("pipeline[{}]", idx);
+        if let Some(channel) = step.remove("channel") {
+            step.insert("channels".into(), Value::Sequence(vec![channel]));
+            changes.push(&path, "replaced channel with channels");
+        }
+        if let Some(names) = step.get_mut("names").and_then(Value::as_sequence_mut) {
+            let before = names.len();
+            names.retain(|name| !removed.contains(name));
+            if names.len() != before {
+                changes.push(&path, "removed references to main Volume filters");
+            }
+        }
+    }
+    // Drop filter steps that no longer do anything.
+    let mut idx = 0;
+    pipeline.retain(|step| {
+        let empty = step
+            .get("names")
+            .and_then(Value::as_sequence)
+            .is_some_and(|names| names.is_empty());
+        if empty {
+            changes.push(&format!("pipeline[{}]", idx), "removed empty filter step");
+        }
+        idx += 1;
+        !empty
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::*;
+
+    const V1: &str = r#"---
+devices:
+  samplerate: 96000
+  chunksize: 2048
+  enable_resampling: true
+  resampler_type: BalancedAsync
+  capture_samplerate: 44100
+  capture:
+    type: File
+    channels: 2
+    filename: "/dev/stdin"
+    format: S24LE3
+  playback:
+    type: Alsa
+    channels: 2
+    device: "hw:0"
+    format: S24LE
+filters:
+  vol:
+    type: Volume
+    parameters:
+      ramp_time: 200
+  fir:
+    type: Conv
+    parameters:
+      type: File
+      filename: fir.raw
+      format: FLOAT32LE
+  tpdf:
+    type: Dither
+    parameters:
+      type: Simple
+      bits: 16
+pipeline:
+  - type: Filter
+    channel: 0
+    names:
+      - vol
+      - fir
+  - type: Filter
+    channel: 1
+    names:
+      - vol
+  - type: Filter
+    channel: 1
+    names:
+      - tpdf
+"#;
+
+    #[test]
+    fn test_migrate_v1() {
+        let migration = migrate_yaml(V1).unwrap();
+        let config = &migration.config;
+        assert_eq!(
+            config.devices.resampler,
+            Some(Resampler::AsyncSinc(AsyncSincParameters::Profile {
+                profile: AsyncSincProfile::Balanced
+            }))
+        );
+        assert!(matches!(
+            &config.devices.capture,
+            CaptureDevice::RawFile(raw) if raw.format == BinarySampleFormat::S24_3_LE
+        ));
+        let filters = config.filters.as_ref().unwrap();
+        assert!(!filters.contains_key("vol"));
+        assert!(matches!(
+            &filters["fir"],
+            Filter::Conv {
+                parameters: ConvParameters::Raw(raw),
+                ..
+            } if raw.format == Some(FileSampleFormat::F32_LE)
+        ));
+        let pipeline = config.pipeline.as_ref().unwrap();
+        assert_eq!(pipeline.len(), 2);
+        assert!(matches!(
+            &pipeline[0],
+            PipelineStep::Filter(step) if step.channels == Some(vec![0]) && step.names == vec!["fir"]
+        ));
+        assert_eq!(config.validate(), Ok(()));
+
+        let changes: Vec<String> = migration.changes.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            changes,
+            vec![
+                "devices.resampler: replaced enable_resampling and resampler_type",
+                "devices.capture: renamed type File to RawFile",
+                "devices.capture: renamed format S24LE3 to S24_3_LE",
+                "devices.playback: renamed format S24LE to S24_4_LE",
+                "filters.vol: removed Volume filter for the main fader, main volume is now applied by default",
+                "filters.fir: renamed type File to Raw",
+                "filters.fir: renamed format FLOAT32LE to F32_LE",
+                "filters.tpdf: renamed type Simple to Highpass",
+                "pipeline[0]: replaced channel with channels",
+                "pipeline[0]: removed references to main Volume filters",
+                "pipeline[1]: replaced channel with channels",
+                "pipeline[1]: removed references to main Volume filters",
+                "pipeline[2]: replaced channel with channels",
+                "pipeline[1]: removed empty filter step",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_migrate_is_idempotent() {
+        let migrated = migrate_yaml(V1).unwrap().config.to_yaml_string().unwrap();
+        let again = migrate_yaml(&migrated).unwrap();
+        assert!(again.changes.is_empty());
+
+        let err = migrate_yaml("devices: [1, 2]").unwrap_err();
+        assert!(matches!(err, MigrationError::Invalid { .. }));
+    }
+}
@@ -0,0 +1,530 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use indexmap::IndexMap;
+
+use crate::dsp::ResponseError;
+use crate::types::*;
+
+// Responses closer than this, relative to their magnitude, are considered equal.
+const TOLERANCE: f64 = 1e-9;
+
+/// What `Configuration::optimize` may change beside the rewrites it always makes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OptimizeOptions {
+    /// Remove bypassed steps. They do not process anything, but keeping them
+    /// lets a step be switched back on at runtime without reloading the config.
+    pub drop_bypassed: bool,
+}
+
+/// One rewrite made while optimizing a config.
+/// Pipeline paths refer to the steps of the original config.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rewrite {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Rewrite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// An optimized config, with the list of rewrites that were made.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Optimization {
+    pub config: Configuration,
+    pub rewrites: Vec<Rewrite>,
+}
+
+impl Optimization {
+    /// Check with the frequency responses at `freqs` that the optimized config
+    /// sounds the same as `original`, for every capture to playback path.
+    pub fn is_equivalent(
+        &self,
+        original: &Configuration,
+        freqs: &[f64],
+    ) -> Result<bool, ResponseError> {
+        let before = original.frequency_response(freqs)?;
+        let after = self.config.frequency_response(freqs)?;
+        if before.paths.len() != after.paths.len() {
+            return Ok(false);
+        }
+        let same = before.paths.iter().zip(&after.paths).all(|(a, b)| {
+            a.len() == b.len()
+                && a.iter().zip(b).all(|(a, b)| {
+                    a.values
+                        .iter()
+                        .zip(&b.values)
+                        .all(|(a, b)| (a - b).norm() <= TOLERANCE * a.norm().max(1.0))
+                })
+        });
+        Ok(same)
+    }
+}
+
+// A pipeline step with the index it had in the original config.
+type Step = (usize, PipelineStep);
+
+impl Configuration {
+    /// Simplify the config without changing how it sounds:
+    /// chained mixers are folded into one, adjacent filter steps on the same channels
+    /// are merged, consecutive gains are combined and unused definitions are removed.
+    /// Filters, mixers and processors referenced by the result are left untouched,
+    /// new ones are added for the merged gains and folded mixers.
+    pub fn optimize(&self, options: &OptimizeOptions) -> Optimization {
+        let mut config = self.clone();
+        let mut rewrites = Vec::new();
+        let mut steps: Vec<Step> = config
+            .pipeline
+            .take()
+            .map(|pipeline| pipeline.into_iter().enumerate().collect())
+            .unwrap_or_default();
+
+        if options.drop_bypassed {
+            steps.retain(|(index, step)| {
+                if step.is_bypassed() {
+                    rewrites.push(rewrite(*index, "removed bypassed step"));
+                }
+                !step.is_bypassed()
+            });
+        }
+        if let Some(mixers) = config.mixers.as_mut() {
+            steps = fold_mixers(steps, mixers, &mut rewrites);
+        }
+        steps = merge_filter_steps(steps, &mut rewrites);
+        if let Some(filters) = config.filters.as_mut() {
+            merge_gains(&mut steps, filters, &mut rewrites);
+        }
+        if self.pipeline.is_some() {
+            config.pipeline = Some(steps.into_iter().map(|(_, step)| step).collect());
+        }
+        remove_unused(&mut config, &mut rewrites);
+        Optimization { config, rewrites }
+    }
+}
+
+fn rewrite(index: usize, message: impl Into<String>) -> Rewrite {
+    Rewrite {
+        path: format!("pipeline[{}]", index),
+        message: message.into(),
+    }
+}
+
+// Add `base` to the names, with a number appended if it is taken.
+fn unique_name<T>(map: &IndexMap<String, T>, base: String) -> String {
+    if !map.contains_key(&base) {
+        return base;
+    }
+    (2..)
+        .map(|n| format!("{}_{}", base, n))
+        .find(|name| !map.contains_key(name))
+        .unwrap()
+}
+
+// --- Mixers ---
+
+fn active_mixer<'a>(step: &PipelineStep, mixers: &'a IndexMap<String, Mixer>) -> Option<&'a str> {
+    match step {
+        PipelineStep::Mixer(step) if !step.bypassed.unwrap_or(false) => mixers
+            .get_key_value(&step.name)
+            .map(|(name, _)| name.as_str()),
+        _ => None,
+    }
+}
+
+// Replace each run of consecutive mixer steps by a single step,
+// and remove runs that do nothing.
+fn fold_mixers(
+    steps: Vec<Step>,
+    mixers: &mut IndexMap<String, Mixer>,
+    rewrites: &mut Vec<Rewrite>,
+) -> Vec<Step> {
+    let mut result: Vec<Step> = Vec::new();
+    let mut steps = steps.into_iter().peekable();
+    while let Some((index, step)) = steps.next() {
+        let Some(first) = active_mixer(&step, mixers).map(str::to_string) else {
+            result.push((index, step));
+            continue;
+        };
+        let mut run = vec![(index, step)];
+        let mut names = vec![first];
+        let mut mixer = mixers[&names[0]].clone();
+        while let Some(next) = steps
+            .peek()
+            .and_then(|(_, step)| active_mixer(step, mixers))
+        {
+            let Ok(composed) = mixer.compose(&mixers[next]) else {
+                break;
+            };
+            mixer = composed;
+            names.push(next.to_string());
+            run.push(steps.next().unwrap());
+        }
+        if mixer.is_identity() {
+            let message = if names.len() == 1 {
+                format!(
+                    "removed mixer {}, it passes all channels unchanged",
+                    names[0]
+                )
+            } else {
+                format!(
+                    "removed mixers {}, together they pass all channels unchanged",
+                    names.join(", ")
+                )
+            };
+            rewrites.push(rewrite(index, message));
+        } else if names.len() == 1 {
+            result.extend(run);
+        } else {
+            let name = unique_name(mixers, names.join("_"));
+            rewrites.push(rewrite(
+                index,
+                format!("folded mixers {} into {}", names.join(", "), name),
+            ));
+            mixers.insert(name.clone(), mixer);
+            result.push((
+                index,
+                PipelineStep::Mixer(PipelineStepMixer {
+                    name,
+                    description: None,
+                    bypassed: None,
+                }),
+            ));
+        }
+    }
+    result
+}
+
+// --- Filters ---
+
+fn merge_filter_steps(steps: Vec<Step>, rewrites: &mut Vec<Rewrite>) -> Vec<Step> {
+    let mut result: Vec<Step> = Vec::new();
+    for (index, step) in steps {
+        if let (Some((first, PipelineStep::Filter(previous))), PipelineStep::Filter(current)) =
+            (result.last_mut(), &step)
+        {
+            if !previous.bypassed.unwrap_or(false)
+                && !current.bypassed.unwrap_or(false)
+                && previous.channels == current.channels
+            {
+                previous.names.extend(current.names.iter().cloned());
+                if previous.description.is_none() {
+                    previous.description = current.description.clone();
+                }
+                rewrites.push(rewrite(
+                    *first,
+                    format!("merged with pipeline[{}], it uses the same channels", index),
+                ));
+                continue;
+            }
+        }
+        result.push((index, step));
+    }
+    result
+}
+
+fn linear_gain(name: &str, filters: &IndexMap<String, Filter>) -> Option<f64> {
+    match filters.get(name) {
+        Some(Filter::Gain { parameters, .. }) => Some(parameters.linear_gain()),
+        _ => None,
+    }
+}
+
+fn gain_filter(gain: f64) -> Filter {
+    let mut filter = Filter::gain(20.0 * gain.abs().log10());
+    if let Filter::Gain { parameters, .. } = &mut filter {
+        if gain == 0.0 {
+            parameters.gain = 0.0;
+            parameters.mute = Some(true);
+        } else if gain < 0.0 {
+            parameters.inverted = Some(true);
+        }
+    }
+    filter
+}
+
+// Replace each run of consecutive gain filters in a step by a single gain,
+// or drop it when the gains cancel out.
+fn merge_gains(
+    steps: &mut [Step],
+    filters: &mut IndexMap<String, Filter>,
+    rewrites: &mut Vec<Rewrite>,
+) {
+    // Gains added by this pass, reused when another run merges into the same gain.
+    let mut added: Vec<String> = Vec::new();
+    for (index, step) in steps.iter_mut() {
+        let PipelineStep::Filter(step) = step else {
+            continue;
+        };
+        let mut names: Vec<String> = Vec::new();
+        let mut position = 0;
+        while position < step.names.len() {
+            let run: Vec<&String> = step.names[position..]
+                .iter()
+                .take_while(|name| linear_gain(name, filters).is_some())
+                .collect();
+            if run.len() < 2 {
+                names.push(step.names[position].clone());
+                position += 1;
+                continue;
+            }
+            let gain: f64 = run
+                .iter()
+                .filter_map(|name| linear_gain(name, filters))
+                .product();
+            let merged: Vec<&str> = run.iter().map(|name| name.as_str()).collect();
+            let path = format!("pipeline[{}].names", index);
+            if (gain - 1.0).abs() < TOLERANCE {
+                rewrites.push(Rewrite {
+                    path,
+                    message: format!("removed gains {}, they cancel out", merged.join(", ")),
+                });
+            } else {
+                let filter = gain_filter(gain);
+                let name = match added.iter().find(|name| filters[name.as_str()] == filter) {
+                    Some(name) => name.clone(),
+                    None => {
+                        let name = unique_name(filters, merged.join("_"));
+                        filters.insert(name.clone(), filter);
+                        added.push(name.clone());
+                        name
+                    }
+                };
+                rewrites.push(Rewrite {
+                    path,
+                    message: format!("merged gains {} into {}", merged.join(", "), name),
+                });
+                names.push(name);
+            }
+            position += run.len();
+        }
+        step.names = names;
+    }
+}
+
+// --- Unused definitions ---
+
+fn remove_unused(config: &mut Configuration, rewrites: &mut Vec<Rewrite>) {
+    let mut mixers = HashSet::new();
+    let mut filters = HashSet::new();
+    let mut processors = HashSet::new();
+    for step in config.pipeline.iter().flatten() {
+        match step {
+            PipelineStep::Mixer(step) => {
+                mixers.insert(step.name.clone());
+            }
+            PipelineStep::Filter(step) => filters.extend(step.names.iter().cloned()),
+            PipelineStep::Processor(step) => {
+                processors.insert(step.name.clone());
+            }
+        }
+    }
+    prune("mixers", &mut config.mixers, &mixers, rewrites);
+    prune("filters", &mut config.filters, &filters, rewrites);
+    prune("processors", &mut config.processors, &processors, rewrites);
+}
+
+// Remove the definitions that are not used, and the whole section when it becomes empty.
+fn prune<T>(
+    section: &str,
+    map: &mut Option<IndexMap<String, T>>,
+    used: &HashSet<String>,
+    rewrites: &mut Vec<Rewrite>,
+) {
+    let Some(items) = map else {
+        return;
+    };
+    let before = items.len();
+    items.retain(|name, _| {
+        if !used.contains(name) {
+            rewrites.push(Rewrite {
+                path: format!("{}.{}", section, name),
+                message: "removed, it is not used in the pipeline".to_string(),
+            });
+        }
+        used.contains(name)
+    });
+    if items.is_empty() && before > 0 {
+        *map = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"---
+devices:
+  samplerate: 48000
+  chunksize: 1024
+  capture:
+    type: Stdin
+    channels: 2
+    format: S16_LE
+  playback:
+    type: Stdout
+    channels: 2
+    format: S16_LE
+filters:
+  trim:
+    type: Gain
+    parameters:
+      gain: -3
+  boost:
+    type: Gain
+    parameters:
+      gain: 1.5
+      scale: linear
+      inverted: true
+  undo:
+    type: Gain
+    parameters:
+      gain: 3
+  bass:
+    type: Biquad
+    parameters:
+      type: Lowshelf
+      freq: 100
+      gain: 6
+      q: 0.7
+  spare:
+    type: Delay
+    parameters:
+      delay: 1
+mixers:
+  swap:
+    channels:
+      in: 2
+      out: 2
+    mapping:
+      - dest: 0
+        sources:
+          - channel: 1
+      - dest: 1
+        sources:
+          - channel: 0
+  mono:
+    channels:
+      in: 2
+      out: 2
+    mapping:
+      - dest: 0
+        sources:
+          - channel: 0
+            gain: -6
+          - channel: 1
+            gain: -6
+      - dest: 1
+        sources:
+          - channel: 1
+pipeline:
+  - type: Filter
+    channels: [0]
+    names: [trim, boost]
+  - type: Filter
+    channels: [0]
+    names: [bass]
+  - type: Filter
+    channels: [1]
+    names: [trim, undo, bass]
+  - type: Mixer
+    name: swap
+  - type: Mixer
+    name: mono
+  - type: Filter
+    names: [bass]
+    bypassed: true
+  - type: Filter
+    channels: [1]
+    names: [trim, boost]
+"#;
+
+    #[test]
+    fn test_optimize() {
+        let original = Configuration::from_yaml_string(CONFIG).unwrap();
+        let optimization = original.optimize(&OptimizeOptions::default());
+        let messages: Vec<String> = optimization
+            .rewrites
+            .iter()
+            .map(|r| r.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "pipeline[3]: folded mixers swap, mono into swap_mono",
+                "pipeline[0]: merged with pipeline[1], it uses the same channels",
+                "pipeline[0].names: merged gains trim, boost into trim_boost",
+                "pipeline[2].names: removed gains trim, undo, they cancel out",
+                "pipeline[6].names: merged gains trim, boost into trim_boost",
+                "mixers.swap: removed, it is not used in the pipeline",
+                "mixers.mono: removed, it is not used in the pipeline",
+                "filters.trim: removed, it is not used in the pipeline",
+                "filters.boost: removed, it is not used in the pipeline",
+                "filters.undo: removed, it is not used in the pipeline",
+                "filters.spare: removed, it is not used in the pipeline",
+            ]
+        );
+
+        let config = &optimization.config;
+        let pipeline = config.pipeline.as_ref().unwrap();
+        assert_eq!(pipeline.len(), 5);
+        match &pipeline[0] {
+            PipelineStep::Filter(step) => assert_eq!(step.names, vec!["trim_boost", "bass"]),
+            _ => panic!("Expected filter step"),
+        }
+        // The repeated run reuses the merged gain instead of adding a copy.
+        match &pipeline[4] {
+            PipelineStep::Filter(step) => assert_eq!(step.names, vec!["trim_boost"]),
+            _ => panic!("Expected filter step"),
+        }
+        assert!(!config
+            .filters
+            .as_ref()
+            .unwrap()
+            .contains_key("trim_boost_2"));
+        match &config.filters.as_ref().unwrap()["trim_boost"] {
+            Filter::Gain { parameters, .. } => {
+                assert!((parameters.linear_gain() + 1.5 * 0.707946).abs() < 1e-6)
+            }
+            _ => panic!("Expected gain filter"),
+        }
+        let freqs = [20.0, 100.0, 1000.0, 10000.0];
+        assert!(optimization.is_equivalent(&original, &freqs).unwrap());
+
+        let dropped = original.optimize(&OptimizeOptions {
+            drop_bypassed: true,
+        });
+        assert_eq!(dropped.config.pipeline.as_ref().unwrap().len(), 4);
+        assert_eq!(
+            dropped.rewrites[0].to_string(),
+            "pipeline[5]: removed bypassed step"
+        );
+        assert!(dropped.is_equivalent(&original, &freqs).unwrap());
+
+        // Optimizing again finds nothing more to do.
+        let again = optimization.config.optimize(&OptimizeOptions::default());
+        assert!(again.rewrites.is_empty());
+        assert_eq!(again.config, optimization.config);
+    }
+
+    #[test]
+    fn test_identity_mixers_and_detection() {
+        let original =
+            Configuration::from_yaml_string(&CONFIG.replace("name: mono", "name: swap")).unwrap();
+        let optimization = original.optimize(&OptimizeOptions::default());
+        assert_eq!(
+            optimization.rewrites[0].to_string(),
+            "pipeline[3]: removed mixers swap, swap, together they pass all channels unchanged"
+        );
+        assert!(optimization.config.mixers.is_none());
+        assert!(optimization
+            .is_equivalent(&original, &[100.0, 1000.0])
+            .unwrap());
+
+        let mut broken = optimization.clone();
+        broken.config.filters.as_mut().unwrap()["trim_boost"] = Filter::gain(-3.0);
+        assert!(!broken.is_equivalent(&original, &[100.0, 1000.0]).unwrap());
+    }
+}
@@ -0,0 +1,193 @@
+use crate::types::*;
+
+// 20 * log10(0.5), for summing two channels without clipping.
+const HALF: f64 = -6.020599913279624;
+// 20 * log10(1 / sqrt(2)), the -3 dB of ITU-R BS.775.
+const MINUS_3DB: f64 = -3.010299956639812;
+
+/// Channel order of 5.1 input, as in WAV and ALSA.
+pub const SURROUND_51_LABELS: [&str; 6] = ["L", "R", "C", "LFE", "SL", "SR"];
+
+fn db(linear: f64) -> f64 {
+    20.0 * linear.log10()
+}
+
+// Build a mixer from (dest, source, gain in dB) routes.
+fn preset(
+    description: &str,
+    inputs: usize,
+    labels: Vec<String>,
+    routes: &[(usize, usize, f64)],
+) -> Mixer {
+    let mut mapping: Vec<MixerMapping> = Vec::new();
+    for &(dest, channel, gain) in routes {
+        let source = MixerSource {
+            channel,
+            gain: Some(gain),
+            inverted: None,
+            mute: None,
+            scale: Some(GainScale::Decibel),
+        };
+        match mapping.iter_mut().find(|m| m.dest == dest) {
+            Some(existing) => existing.sources.push(source),
+            None => mapping.push(MixerMapping {
+                dest,
+                sources: vec![source],
+                mute: None,
+            }),
+        }
+    }
+    Mixer {
+        description: Some(description.to_string()),
+        channels: MixerChannels {
+            r#in: inputs,
+            out: labels.len(),
+        },
+        mapping,
+        labels: Some(labels.into_iter().map(Some).collect()),
+    }
+}
+
+fn labels(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+/// Left and right summed at -6 dB each.
+pub fn stereo_to_mono() -> Mixer {
+    preset(
+        "Stereo to mono",
+        2,
+        labels(&["M"]),
+        &[(0, 0, HALF), (0, 1, HALF)],
+    )
+}
+
+/// ITU-R BS.775 downmix of 5.1, in the order of `SURROUND_51_LABELS`, to stereo.
+/// Center and surrounds are mixed in at -3 dB and LFE is dropped.
+/// The standard coefficients can clip on loud material, with `normalize`
+/// all gains are lowered so that each output sums to unity (about -7.7 dB).
+pub fn surround_51_to_stereo(normalize: bool) -> Mixer {
+    let trim = if normalize {
+        -db(1.0 + 2.0 * 0.5_f64.sqrt())
+    } else {
+        0.0
+    };
+    preset(
+        "5.1 to stereo downmix, ITU-R BS.775",
+        6,
+        labels(&["L", "R"]),
+        &[
+            (0, 0, trim),
+            (0, 2, MINUS_3DB + trim),
+            (0, 4, MINUS_3DB + trim),
+            (1, 1, trim),
+            (1, 2, MINUS_3DB + trim),
+            (1, 5, MINUS_3DB + trim),
+        ],
+    )
+}
+
+/// Left and right passed through, with a third subwoofer channel
+/// summing both at -6 dB. The crossover filters go in the pipeline.
+pub fn stereo_to_2_1() -> Mixer {
+    preset(
+        "Stereo to 2.1",
+        2,
+        labels(&["L", "R", "LFE"]),
+        &[(0, 0, 0.0), (1, 1, 0.0), (2, 0, HALF), (2, 1, HALF)],
+    )
+}
+
+/// Left and right sent to both the front and the rear pair.
+pub fn stereo_to_4_0() -> Mixer {
+    preset(
+        "Stereo to 4.0",
+        2,
+        labels(&["FL", "FR", "RL", "RR"]),
+        &[(0, 0, 0.0), (1, 1, 0.0), (2, 0, 0.0), (3, 1, 0.0)],
+    )
+}
+
+/// Left and right swapped, output 0 plays the right input.
+pub fn swap_stereo() -> Mixer {
+    preset(
+        "Swap left and right",
+        2,
+        labels(&["R", "L"]),
+        &[(0, 1, 0.0), (1, 0, 0.0)],
+    )
+}
+
+/// `copies` copies of all input channels, for example to feed two amplifiers
+/// with the same stereo signal. Output `n` plays input `n % channels`.
+pub fn duplicate(channels: usize, copies: usize) -> Mixer {
+    let names: Vec<String> = match channels {
+        1 => labels(&["M"]),
+        2 => labels(&["L", "R"]),
+        _ => (0..channels).map(|channel| channel.to_string()).collect(),
+    };
+    let mut output_labels = Vec::new();
+    let mut routes = Vec::new();
+    for copy in 0..copies {
+        for (channel, name) in names.iter().enumerate() {
+            routes.push((output_labels.len(), channel, 0.0));
+            output_labels.push(format!("{} {}", name, copy + 1));
+        }
+    }
+    preset(
+        &format!("{} copies of {} channels", copies, channels),
+        channels,
+        output_labels,
+        &routes,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_gains(mixer: &Mixer, expected: &[&[f64]]) {
+        let matrix = mixer.matrix();
+        assert_eq!(matrix.outputs(), expected.len());
+        for (row, expected) in matrix.rows().iter().zip(expected) {
+            assert_eq!(row.len(), expected.len());
+            for (gain, expected) in row.iter().zip(*expected) {
+                assert!((gain - expected).abs() < 1e-12, "{:?}", matrix.rows());
+            }
+        }
+        let labels = mixer.labels.as_ref().unwrap();
+        assert_eq!(labels.len(), mixer.channels.out);
+    }
+
+    #[test]
+    fn test_downmix_coefficients() {
+        let c = 0.5_f64.sqrt();
+        assert_gains(&stereo_to_mono(), &[&[0.5, 0.5]]);
+        assert_gains(
+            &surround_51_to_stereo(false),
+            &[&[1.0, 0.0, c, 0.0, c, 0.0], &[0.0, 1.0, c, 0.0, 0.0, c]],
+        );
+        let normalized = surround_51_to_stereo(true).matrix();
+        assert!((normalized.rows()[0].iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        assert!((normalized.gain(0, 2) / normalized.gain(0, 0) - c).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_upmix_and_routing() {
+        assert_gains(&stereo_to_2_1(), &[&[1.0, 0.0], &[0.0, 1.0], &[0.5, 0.5]]);
+        assert_gains(
+            &stereo_to_4_0(),
+            &[&[1.0, 0.0], &[0.0, 1.0], &[1.0, 0.0], &[0.0, 1.0]],
+        );
+        let swap = swap_stereo();
+        assert_gains(&swap, &[&[0.0, 1.0], &[1.0, 0.0]]);
+        assert!(swap.compose(&swap).unwrap().is_identity());
+
+        let bi_amp = duplicate(2, 2);
+        assert_gains(
+            &bi_amp,
+            &[&[1.0, 0.0], &[0.0, 1.0], &[1.0, 0.0], &[0.0, 1.0]],
+        );
+        assert_eq!(bi_amp.labels.unwrap()[2], Some("L 2".to_string()));
+    }
+}
@@ -0,0 +1,131 @@
+use serde_json::Value;
+use utoipa::openapi::{Components, ComponentsBuilder, InfoBuilder, OpenApi, OpenApiBuilder};
+use utoipa::ToSchema;
+
+use crate::types::Configuration;
+
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Schemas of `Configuration` and every type it refers to.
+pub fn components() -> Components {
+    let mut schemas = Vec::new();
+    Configuration::schemas(&mut schemas);
+    ComponentsBuilder::new()
+        .schema_from::<Configuration>()
+        .schemas_from_iter(schemas)
+        .build()
+}
+
+/// OpenAPI document with the configuration types as components.
+pub fn openapi() -> OpenApi {
+    OpenApiBuilder::new()
+        .info(
+            InfoBuilder::new()
+                .title("CamillaDSP configuration")
+                .version(env!("CARGO_PKG_VERSION"))
+                .build(),
+        )
+        .components(Some(components()))
+        .build()
+}
+
+fn rewrite_refs(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                if key == "$ref" {
+                    if let Value::String(reference) = item {
+                        *reference = reference.replace("#/components/schemas/", "#/$defs/");
+                    }
+                } else {
+                    rewrite_refs(item);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(rewrite_refs),
+        _ => {}
+    }
+}
+
+/// Standalone JSON Schema for a configuration file, with the referenced types under `$defs`.
+pub fn configuration_json_schema() -> Value {
+    let components = components();
+    let mut root = serde_json::to_value(&components.schemas[Configuration::name().as_ref()])
+        .expect("schema serializes to json");
+    let mut defs = serde_json::Map::new();
+    for (name, schema) in &components.schemas {
+        defs.insert(
+            name.clone(),
+            serde_json::to_value(schema).expect("schema serializes to json"),
+        );
+    }
+    if let Value::Object(map) = &mut root {
+        map.insert(
+            "$schema".to_string(),
+            Value::String(JSON_SCHEMA_DIALECT.to_string()),
+        );
+        map.insert(
+            "title".to_string(),
+            Value::String("CamillaDSP configuration".to_string()),
+        );
+        map.insert("$defs".to_string(), Value::Object(defs));
+    }
+    rewrite_refs(&mut root);
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect_refs(value: &Value, refs: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                for (key, item) in map {
+                    match (key.as_str(), item) {
+                        ("$ref", Value::String(reference)) => refs.push(reference.clone()),
+                        _ => collect_refs(item, refs),
+                    }
+                }
+            }
+            Value::Array(items) => items.iter().for_each(|item| collect_refs(item, refs)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn test_openapi_contains_all_referenced_schemas() {
+        let doc = openapi();
+        let json = serde_json::to_value(&doc).unwrap();
+        let schemas = &json["components"]["schemas"];
+        for name in ["Configuration", "Filter", "CaptureDevice", "Resampler"] {
+            assert!(schemas.get(name).is_some(), "missing {}", name);
+        }
+        let mut refs = Vec::new();
+        collect_refs(&json, &mut refs);
+        assert!(!refs.is_empty());
+        for reference in refs {
+            let name = reference.trim_start_matches("#/components/schemas/");
+            assert!(schemas.get(name).is_some(), "dangling {}", reference);
+        }
+    }
+
+    #[test]
+    fn test_json_schema_is_self_contained() {
+        let schema = configuration_json_schema();
+        assert_eq!(schema["$schema"], JSON_SCHEMA_DIALECT);
+        assert!(schema["properties"]["devices"].is_object());
+        let mut refs = Vec::new();
+        collect_refs(&schema, &mut refs);
+        for reference in refs {
+            let name = reference
+                .strip_prefix("#/$defs/")
+                .unwrap_or_else(|| panic!("unexpected ref {}", reference));
+            assert!(
+                schema["$defs"].get(name).is_some(),
+                "dangling {}",
+                reference
+            );
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::websocket::Fader;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StateFileError {
+    Io(String),
+    Parse(String),
+    FaderCount { field: &'static str, found: usize },
+}
+
+impl fmt::Display for StateFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateFileError::Io(reason) => write!(f, "could not access statefile: {}", reason),
+            StateFileError::Parse(reason) => write!(f, "could not parse statefile: {}", reason),
+            StateFileError::FaderCount { field, found } => write!(
+                f,
+                "{} must have {} values, one per fader, found {}",
+                field,
+                StateFile::FADERS,
+                found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StateFileError {}
+
+/// The state CamillaDSP persists between runs.
+/// `volume` and `mute` hold one value per fader, in the order of [`Fader::index`]:
+/// Main first and then Aux1 to Aux4.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct StateFile {
+    #[serde(default)]
+    pub config_path: Option<String>,
+    pub mute: Vec<bool>,
+    pub volume: Vec<f32>,
+}
+
+impl Default for StateFile {
+    fn default() -> Self {
+        StateFile {
+            config_path: None,
+            mute: vec![false; StateFile::FADERS],
+            volume: vec![0.0; StateFile::FADERS],
+        }
+    }
+}
+
+impl StateFile {
+    pub const FADERS: usize = 5;
+
+    pub fn validate(&self) -> Result<(), StateFileError> {
+        if self.mute.len() != StateFile::FADERS {
+            return Err(StateFileError::FaderCount {
+                field: "mute",
+                found: self.mute.len(),
+            });
+        }
+        if self.volume.len() != StateFile::FADERS {
+            return Err(StateFileError::FaderCount {
+                field: "volume",
+                found: self.volume.len(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn from_yaml_string(yaml: &str) -> Result<Self, StateFileError> {
+        let state: StateFile =
+            serde_yaml::from_str(yaml).map_err(|e| StateFileError::Parse(e.to_string()))?;
+        state.validate()?;
+        Ok(state)
+    }
+
+    pub fn to_yaml_string(&self) -> Result<String, StateFileError> {
+        self.validate()?;
+        serde_yaml::to_string(self).map_err(|e| StateFileError::Parse(e.to_string()))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, StateFileError> {
+        let yaml = fs::read_to_string(path).map_err(|e| StateFileError::Io(e.to_string()))?;
+        StateFile::from_yaml_string(&yaml)
+    }
+
+    /// Write the statefile atomically: the new content is written to a temporary
+    /// file next to it, which then replaces the old file with a rename.
+    /// CamillaDSP therefore never reads a partly written file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), StateFileError> {
+        let path = path.as_ref();
+        let yaml = self.to_yaml_string()?;
+        let io = |e: std::io::Error| StateFileError::Io(e.to_string());
+        let name = path
+            .file_name()
+            .ok_or_else(|| StateFileError::Io(format!("{} is not a file", path.display())))?;
+        let mut temp_name = std::ffi::OsString::from(".");
+        temp_name.push(name);
+        temp_name.push(".tmp");
+        let temp = path.with_file_name(temp_name);
+        let result = (|| {
+            let mut file = fs::File::create(&temp)?;
+            file.write_all(yaml.as_bytes())?;
+            file.sync_all()?;
+            fs::rename(&temp, path)
+        })();
+        if result.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+        result.map_err(io)
+    }
+
+    /// Volume of a fader in dB, `None` when `volume` was changed to hold too few values.
+    pub fn volume(&self, fader: Fader) -> Option<f32> {
+        self.volume.get(fader.index()).copied()
+    }
+
+    pub fn set_volume(&mut self, fader: Fader, volume: f32) -> Result<(), StateFileError> {
+        self.validate()?;
+        self.volume[fader.index()] = volume;
+        Ok(())
+    }
+
+    /// Mute state of a fader, `None` when `mute` was changed to hold too few values.
+    pub fn mute(&self, fader: Fader) -> Option<bool> {
+        self.mute.get(fader.index()).copied()
+    }
+
+    pub fn set_mute(&mut self, fader: Fader, mute: bool) -> Result<(), StateFileError> {
+        self.validate()?;
+        self.mute[fader.index()] = mute;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::VolumeFader;
+
+    #[test]
+    fn test_load_and_save() {
+        let dir = std::env::temp_dir().join(format!("camilladsp-statefile-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("statefile.yml");
+        fs::write(
+            &path,
+            "config_path: /etc/camilladsp/active.yml\nmute: [false, true, false, false, false]\nvolume: [-12.0, 0.0, -3.5, 0.0, 0.0]\n",
+        )
+        .unwrap();
+
+        let mut state = StateFile::load(&path).unwrap();
+        assert_eq!(state.volume(Fader::Main), Some(-12.0));
+        assert_eq!(state.mute(Fader::Aux(VolumeFader::Aux1)), Some(true));
+        state.set_volume(Fader::Main, -20.0).unwrap();
+        state
+            .set_mute(Fader::Aux(VolumeFader::Aux1), false)
+            .unwrap();
+        state.config_path = Some("/etc/camilladsp/night.yml".to_string());
+        state.save(&path).unwrap();
+
+        assert_eq!(StateFile::load(&path).unwrap(), state);
+        let names: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec!["statefile.yml"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_fader_count() {
+        assert_eq!(
+            StateFile::from_yaml_string(
+                "config_path: null\nmute: [false]\nvolume: [0, 0, 0, 0, 0]\n"
+            ),
+            Err(StateFileError::FaderCount {
+                field: "mute",
+                found: 1
+            })
+        );
+        let mut state = StateFile::default();
+        state.volume.truncate(2);
+        assert_eq!(state.volume(Fader::Aux(VolumeFader::Aux4)), None);
+        assert_eq!(
+            state.set_volume(Fader::Main, -3.0),
+            Err(StateFileError::FaderCount {
+                field: "volume",
+                found: 2
+            })
+        );
+        assert!(state
+            .save(std::env::temp_dir().join("never-written.yml"))
+            .is_err());
+        assert_eq!(
+            StateFile::from_yaml_string(&StateFile::default().to_yaml_string().unwrap()),
+            Ok(StateFile::default())
+        );
+    }
+}
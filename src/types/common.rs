@@ -27,6 +27,20 @@ pub enum BinarySampleFormat {
     F64_LE,
 }
 
+impl BinarySampleFormat {
+    pub fn bits(self) -> usize {
+        match self {
+            BinarySampleFormat::S16_LE => 16,
+            BinarySampleFormat::S24_4_RJ_LE
+            | BinarySampleFormat::S24_4_LJ_LE
+            | BinarySampleFormat::S24_3_LE
+            | BinarySampleFormat::F32_LE => 24,
+            BinarySampleFormat::S32_LE => 32,
+            BinarySampleFormat::F64_LE => 53,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq, utoipa::ToSchema)]
 #[serde(deny_unknown_fields)]
 #[allow(non_camel_case_types)]
@@ -39,6 +53,20 @@ pub enum AlsaSampleFormat {
     F64_LE,
 }
 
+impl AlsaSampleFormat {
+    /// Resolution of a sample in bits, for float formats the precision of the mantissa.
+    pub fn bits(self) -> usize {
+        match self {
+            AlsaSampleFormat::S16_LE => 16,
+            AlsaSampleFormat::S24_3_LE | AlsaSampleFormat::S24_4_LE | AlsaSampleFormat::F32_LE => {
+                24
+            }
+            AlsaSampleFormat::S32_LE => 32,
+            AlsaSampleFormat::F64_LE => 53,
+        }
+    }
+}
+
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq, utoipa::ToSchema)]
 #[serde(deny_unknown_fields)]
@@ -49,6 +77,16 @@ pub enum WasapiSampleFormat {
     F32,
 }
 
+impl WasapiSampleFormat {
+    pub fn bits(self) -> usize {
+        match self {
+            WasapiSampleFormat::S16 => 16,
+            WasapiSampleFormat::S24 | WasapiSampleFormat::F32 => 24,
+            WasapiSampleFormat::S32 => 32,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq, utoipa::ToSchema)]
 #[serde(deny_unknown_fields)]
 #[allow(non_camel_case_types)]
@@ -61,6 +99,19 @@ pub enum AsioSampleFormat {
     F64_LE,
 }
 
+impl AsioSampleFormat {
+    pub fn bits(self) -> usize {
+        match self {
+            AsioSampleFormat::S16_LE => 16,
+            AsioSampleFormat::S24_4_LE | AsioSampleFormat::S24_3_LE | AsioSampleFormat::F32_LE => {
+                24
+            }
+            AsioSampleFormat::S32_LE => 32,
+            AsioSampleFormat::F64_LE => 53,
+        }
+    }
+}
+
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq, utoipa::ToSchema)]
 #[serde(deny_unknown_fields)]
@@ -71,6 +122,16 @@ pub enum CoreAudioSampleFormat {
     F32,
 }
 
+impl CoreAudioSampleFormat {
+    pub fn bits(self) -> usize {
+        match self {
+            CoreAudioSampleFormat::S16 => 16,
+            CoreAudioSampleFormat::S24 | CoreAudioSampleFormat::F32 => 24,
+            CoreAudioSampleFormat::S32 => 32,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq, utoipa::ToSchema)]
 pub enum GainScale {
     #[serde(rename = "linear")]
@@ -92,6 +153,20 @@ pub enum TimeUnit {
     Samples,
 }
 
+impl TimeUnit {
+    pub const SPEED_OF_SOUND: f64 = 343.0;
+
+    pub fn to_samples(self, value: f64, samplerate: usize) -> f64 {
+        let fs = samplerate as f64;
+        match self {
+            TimeUnit::Microseconds => value / 1_000_000.0 * fs,
+            TimeUnit::Milliseconds => value / 1000.0 * fs,
+            TimeUnit::Millimetres => value / 1000.0 / TimeUnit::SPEED_OF_SOUND * fs,
+            TimeUnit::Samples => value,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
 #[serde(deny_unknown_fields)]
 #[serde(tag = "type")]
@@ -1,5 +1,5 @@
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 
 use super::devices::Devices;
 use super::filters::Filter;
@@ -16,11 +16,11 @@ pub struct Configuration {
     pub description: Option<String>,
     pub devices: Devices,
     #[serde(default)]
-    pub mixers: Option<HashMap<String, Mixer>>,
+    pub mixers: Option<IndexMap<String, Mixer>>,
     #[serde(default)]
-    pub filters: Option<HashMap<String, Filter>>,
+    pub filters: Option<IndexMap<String, Filter>>,
     #[serde(default)]
-    pub processors: Option<HashMap<String, Processor>>,
+    pub processors: Option<IndexMap<String, Processor>>,
     #[serde(default)]
     pub pipeline: Option<Vec<PipelineStep>>,
 }
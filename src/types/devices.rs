@@ -284,3 +284,75 @@ pub struct Devices {
     #[serde(default)]
     pub worker_threads: Option<usize>,
 }
+
+impl CaptureDevice {
+    pub fn channels(&self) -> Option<usize> {
+        match self {
+            CaptureDevice::Alsa { channels, .. }
+            | CaptureDevice::Pulse { channels, .. }
+            | CaptureDevice::PipeWire { channels, .. }
+            | CaptureDevice::Jack { channels, .. }
+            | CaptureDevice::SignalGenerator { channels, .. } => Some(*channels),
+            CaptureDevice::Bluez(dev) => Some(dev.channels),
+            CaptureDevice::RawFile(dev) => Some(dev.channels),
+            CaptureDevice::Stdin(dev) => Some(dev.channels),
+            CaptureDevice::CoreAudio(dev) => Some(dev.channels),
+            CaptureDevice::Wasapi(dev) => Some(dev.channels),
+            CaptureDevice::Asio(dev) => Some(dev.channels),
+            // The channel count of a wav file is only known once the file is opened.
+            CaptureDevice::WavFile(_) => None,
+        }
+    }
+
+    /// Names of the capture channels, `None` entries are unnamed.
+    pub fn labels(&self) -> Option<&[Option<String>]> {
+        let labels = match self {
+            CaptureDevice::Alsa { labels, .. }
+            | CaptureDevice::Pulse { labels, .. }
+            | CaptureDevice::PipeWire { labels, .. }
+            | CaptureDevice::Jack { labels, .. }
+            | CaptureDevice::SignalGenerator { labels, .. } => labels,
+            CaptureDevice::Bluez(dev) => &dev.labels,
+            CaptureDevice::RawFile(dev) => &dev.labels,
+            CaptureDevice::WavFile(dev) => &dev.labels,
+            CaptureDevice::Stdin(dev) => &dev.labels,
+            CaptureDevice::CoreAudio(dev) => &dev.labels,
+            CaptureDevice::Wasapi(dev) => &dev.labels,
+            CaptureDevice::Asio(dev) => &dev.labels,
+        };
+        labels.as_deref()
+    }
+}
+
+impl PlaybackDevice {
+    pub fn channels(&self) -> usize {
+        match self {
+            PlaybackDevice::Alsa { channels, .. }
+            | PlaybackDevice::Pulse { channels, .. }
+            | PlaybackDevice::PipeWire { channels, .. }
+            | PlaybackDevice::File { channels, .. }
+            | PlaybackDevice::Stdout { channels, .. }
+            | PlaybackDevice::Jack { channels, .. } => *channels,
+            PlaybackDevice::CoreAudio(dev) => dev.channels,
+            PlaybackDevice::Wasapi(dev) => dev.channels,
+            PlaybackDevice::Asio(dev) => dev.channels,
+        }
+    }
+
+    /// Resolution of the output samples in bits, see `AlsaSampleFormat::bits`.
+    /// `None` when the device or the sound server picks the format.
+    pub fn sample_bits(&self) -> Option<usize> {
+        match self {
+            PlaybackDevice::Alsa { format, .. } => format.map(|f| f.bits()),
+            PlaybackDevice::File { format, .. } | PlaybackDevice::Stdout { format, .. } => {
+                Some(format.bits())
+            }
+            PlaybackDevice::CoreAudio(dev) => dev.format.map(|f| f.bits()),
+            PlaybackDevice::Wasapi(dev) => dev.format.map(|f| f.bits()),
+            PlaybackDevice::Asio(dev) => dev.format.map(|f| f.bits()),
+            PlaybackDevice::Pulse { .. }
+            | PlaybackDevice::PipeWire { .. }
+            | PlaybackDevice::Jack { .. } => None,
+        }
+    }
+}
@@ -339,3 +339,150 @@ pub enum Filter {
         parameters: LimiterParameters,
     },
 }
+
+impl GainParameters {
+    pub fn linear_gain(&self) -> f64 {
+        if self.mute.unwrap_or(false) {
+            return 0.0;
+        }
+        let gain = match self.scale.unwrap_or(GainScale::Decibel) {
+            GainScale::Decibel => 10.0_f64.powf(self.gain / 20.0),
+            GainScale::Linear => self.gain,
+        };
+        if self.inverted.unwrap_or(false) {
+            -gain
+        } else {
+            gain
+        }
+    }
+}
+
+impl DiffEqParameters {
+    /// The `a` and `b` coefficients, with the default `[1.0]` for a missing list.
+    /// Fails when the filter can not be evaluated: `a0` must be non-zero
+    /// and `b` must not be empty.
+    pub fn coefficients(&self) -> Result<(Vec<f64>, Vec<f64>), String> {
+        let a = self.a.clone().unwrap_or_else(|| vec![1.0]);
+        let b = self.b.clone().unwrap_or_else(|| vec![1.0]);
+        if a.first().is_none_or(|a0| *a0 == 0.0) {
+            return Err("DiffEq needs a non-zero a0 coefficient".to_string());
+        }
+        if b.is_empty() {
+            return Err("DiffEq needs at least one b coefficient".to_string());
+        }
+        Ok((a, b))
+    }
+}
+
+impl DitherParameters {
+    pub fn bits(&self) -> usize {
+        match self {
+            DitherParameters::None { bits, .. }
+            | DitherParameters::Flat { bits, .. }
+            | DitherParameters::Highpass { bits, .. }
+            | DitherParameters::Fweighted441 { bits, .. }
+            | DitherParameters::FweightedLong441 { bits, .. }
+            | DitherParameters::FweightedShort441 { bits, .. }
+            | DitherParameters::Gesemann441 { bits, .. }
+            | DitherParameters::Gesemann48 { bits, .. }
+            | DitherParameters::Lipshitz441 { bits, .. }
+            | DitherParameters::LipshitzLong441 { bits, .. }
+            | DitherParameters::Shibata441 { bits, .. }
+            | DitherParameters::ShibataHigh441 { bits, .. }
+            | DitherParameters::ShibataLow441 { bits, .. }
+            | DitherParameters::Shibata48 { bits, .. }
+            | DitherParameters::ShibataHigh48 { bits, .. }
+            | DitherParameters::ShibataLow48 { bits, .. }
+            | DitherParameters::Shibata882 { bits, .. }
+            | DitherParameters::ShibataLow882 { bits, .. }
+            | DitherParameters::Shibata96 { bits, .. }
+            | DitherParameters::ShibataLow96 { bits, .. }
+            | DitherParameters::Shibata192 { bits, .. }
+            | DitherParameters::ShibataLow192 { bits, .. } => *bits,
+        }
+    }
+}
+
+impl Filter {
+    pub fn biquad(parameters: BiquadParameters) -> Self {
+        Filter::Biquad {
+            description: None,
+            parameters,
+        }
+    }
+
+    pub fn biquad_combo(parameters: BiquadComboParameters) -> Self {
+        Filter::BiquadCombo {
+            description: None,
+            parameters,
+        }
+    }
+
+    pub fn peaking(freq: f64, q: f64, gain: f64) -> Self {
+        Filter::biquad(BiquadParameters::Peaking(PeakingWidth::Q { freq, q, gain }))
+    }
+
+    pub fn lowshelf(freq: f64, q: f64, gain: f64) -> Self {
+        Filter::biquad(BiquadParameters::Lowshelf(ShelfSteepness::Q {
+            freq,
+            q,
+            gain,
+        }))
+    }
+
+    pub fn highshelf(freq: f64, q: f64, gain: f64) -> Self {
+        Filter::biquad(BiquadParameters::Highshelf(ShelfSteepness::Q {
+            freq,
+            q,
+            gain,
+        }))
+    }
+
+    pub fn lowpass(freq: f64, q: f64) -> Self {
+        Filter::biquad(BiquadParameters::Lowpass { freq, q })
+    }
+
+    pub fn highpass(freq: f64, q: f64) -> Self {
+        Filter::biquad(BiquadParameters::Highpass { freq, q })
+    }
+
+    /// Gain in dB.
+    pub fn gain(gain: f64) -> Self {
+        Filter::Gain {
+            description: None,
+            parameters: GainParameters {
+                gain,
+                inverted: None,
+                mute: None,
+                scale: Some(GainScale::Decibel),
+            },
+        }
+    }
+
+    pub fn delay(delay: f64, unit: TimeUnit) -> Self {
+        Filter::Delay {
+            description: None,
+            parameters: DelayParameters {
+                delay,
+                unit: Some(unit),
+                subsample: None,
+            },
+        }
+    }
+
+    pub fn with_description(mut self, text: impl Into<String>) -> Self {
+        match &mut self {
+            Filter::Conv { description, .. }
+            | Filter::Biquad { description, .. }
+            | Filter::BiquadCombo { description, .. }
+            | Filter::Delay { description, .. }
+            | Filter::Gain { description, .. }
+            | Filter::Volume { description, .. }
+            | Filter::Loudness { description, .. }
+            | Filter::Dither { description, .. }
+            | Filter::DiffEq { description, .. }
+            | Filter::Limiter { description, .. } => *description = Some(text.into()),
+        }
+        self
+    }
+}
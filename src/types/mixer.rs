@@ -42,3 +42,21 @@ pub struct Mixer {
     #[serde(default)]
     pub labels: Option<Vec<Option<String>>>,
 }
+
+impl MixerSource {
+    pub fn linear_gain(&self) -> f64 {
+        if self.mute.unwrap_or(false) {
+            return 0.0;
+        }
+        let gain = self.gain.unwrap_or(0.0);
+        let gain = match self.scale.unwrap_or(GainScale::Decibel) {
+            GainScale::Decibel => 10.0_f64.powf(gain / 20.0),
+            GainScale::Linear => gain,
+        };
+        if self.inverted.unwrap_or(false) {
+            -gain
+        } else {
+            gain
+        }
+    }
+}
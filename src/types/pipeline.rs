@@ -40,3 +40,14 @@ pub enum PipelineStep {
     Filter(PipelineStepFilter),
     Processor(PipelineStepProcessor),
 }
+
+impl PipelineStep {
+    pub fn is_bypassed(&self) -> bool {
+        let bypassed = match self {
+            PipelineStep::Mixer(step) => step.bypassed,
+            PipelineStep::Filter(step) => step.bypassed,
+            PipelineStep::Processor(step) => step.bypassed,
+        };
+        bypassed.unwrap_or(false)
+    }
+}
@@ -70,3 +70,13 @@ pub enum Processor {
         parameters: RACEParameters,
     },
 }
+
+impl Processor {
+    pub fn channels(&self) -> usize {
+        match self {
+            Processor::Compressor { parameters, .. } => parameters.channels,
+            Processor::NoiseGate { parameters, .. } => parameters.channels,
+            Processor::RACE { parameters, .. } => parameters.channels,
+        }
+    }
+}
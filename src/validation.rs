@@ -0,0 +1,711 @@
+use indexmap::IndexMap;
+use std::fmt;
+
+use crate::types::*;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationErrorKind {
+    MissingFilter(String),
+    MissingMixer(String),
+    MissingProcessor(String),
+    MixerDestOutOfRange { dest: usize, out: usize },
+    MixerSourceOutOfRange { channel: usize, r#in: usize },
+    DuplicateMixerDest(usize),
+    MixerLabelCount { labels: usize, out: usize },
+    ProcessorChannelOutOfRange { channel: usize, channels: usize },
+    DuplicateProcessorChannel(usize),
+    FilterChannelOutOfRange { channel: usize, channels: usize },
+    MixerInputMismatch { expected: usize, found: usize },
+    ProcessorChannelMismatch { expected: usize, found: usize },
+    PlaybackChannelMismatch { expected: usize, found: usize },
+    MissingDevice,
+    DuplicateName(String),
+    FrequencyOutOfRange { freq: f64, nyquist: f64 },
+    ParameterOutOfRange { value: f64, min: f64, max: f64 },
+    DitherBitsOutOfRange { bits: usize, max: usize },
+}
+
+impl fmt::Display for ValidationErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationErrorKind::MissingFilter(name) => {
+                write!(f, "filter '{}' is not defined in 'filters'", name)
+            }
+            ValidationErrorKind::MissingMixer(name) => {
+                write!(f, "mixer '{}' is not defined in 'mixers'", name)
+            }
+            ValidationErrorKind::MissingProcessor(name) => {
+                write!(f, "processor '{}' is not defined in 'processors'", name)
+            }
+            ValidationErrorKind::MixerDestOutOfRange { dest, out } => write!(
+                f,
+                "destination channel {} is out of range, mixer has {} output channels",
+                dest, out
+            ),
+            ValidationErrorKind::MixerSourceOutOfRange { channel, r#in } => write!(
+                f,
+                "source channel {} is out of range, mixer has {} input channels",
+                channel, r#in
+            ),
+            ValidationErrorKind::DuplicateMixerDest(dest) => {
+                write!(f, "destination channel {} is mapped more than once", dest)
+            }
+            ValidationErrorKind::MixerLabelCount { labels, out } => {
+                write!(f, "mixer has {} labels but {} output channels", labels, out)
+            }
+            ValidationErrorKind::ProcessorChannelOutOfRange { channel, channels } => write!(
+                f,
+                "channel {} is out of range, processor has {} channels",
+                channel, channels
+            ),
+            ValidationErrorKind::DuplicateProcessorChannel(channel) => {
+                write!(f, "channel {} is listed more than once", channel)
+            }
+            ValidationErrorKind::FilterChannelOutOfRange { channel, channels } => write!(
+                f,
+                "filter step uses channel {} but only {} channels exist here",
+                channel, channels
+            ),
+            ValidationErrorKind::MixerInputMismatch { expected, found } => write!(
+                f,
+                "mixer expects {} input channels but {} channels exist here",
+                expected, found
+            ),
+            ValidationErrorKind::ProcessorChannelMismatch { expected, found } => write!(
+                f,
+                "processor expects {} channels but {} channels exist here",
+                expected, found
+            ),
+            ValidationErrorKind::PlaybackChannelMismatch { expected, found } => write!(
+                f,
+                "playback device has {} channels but the pipeline ends with {}",
+                expected, found
+            ),
+            ValidationErrorKind::MissingDevice => write!(f, "device is not set"),
+            ValidationErrorKind::DuplicateName(name) => {
+                write!(f, "'{}' is defined more than once", name)
+            }
+            ValidationErrorKind::FrequencyOutOfRange { freq, nyquist } => write!(
+                f,
+                "frequency {} Hz must be above 0 and below the Nyquist frequency of {} Hz",
+                freq, nyquist
+            ),
+            ValidationErrorKind::ParameterOutOfRange { value, min, max } => write!(
+                f,
+                "{} is out of range, must be above {} and at most {}",
+                value, min, max
+            ),
+            ValidationErrorKind::DitherBitsOutOfRange { bits, max } => write!(
+                f,
+                "dither to {} bits is out of range, the playback format allows 2 to {} bits",
+                bits, max
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationError {
+    pub path: String,
+    pub kind: ValidationErrorKind,
+}
+
+impl ValidationError {
+    pub fn new(path: impl Into<String>, kind: ValidationErrorKind) -> Self {
+        ValidationError {
+            path: path.into(),
+            kind,
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.kind)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl Configuration {
+    /// Check the references between `pipeline`, `filters`, `mixers` and `processors`,
+    /// the channel indices used inside mixers and processors, the channel flow
+    /// through the pipeline, and that filter parameters are in range for the sample rate.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        validate_signal(&self.devices, &mut errors);
+        if let Some(filters) = &self.filters {
+            for (name, filter) in filters {
+                validate_filter(
+                    &format!("filters.{}", name),
+                    filter,
+                    &self.devices,
+                    &mut errors,
+                );
+            }
+        }
+        if let Some(mixers) = &self.mixers {
+            for (name, mixer) in mixers {
+                validate_mixer(&format!("mixers.{}", name), mixer, &mut errors);
+            }
+        }
+        if let Some(processors) = &self.processors {
+            for (name, processor) in processors {
+                validate_processor(&format!("processors.{}", name), processor, &mut errors);
+            }
+        }
+        self.validate_pipeline_references(&mut errors);
+        errors.extend(self.channel_flow().errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn validate_pipeline_references(&self, errors: &mut Vec<ValidationError>) {
+        let Some(pipeline) = &self.pipeline else {
+            return;
+        };
+        for (idx, step) in pipeline.iter().enumerate() {
+            match step {
+                PipelineStep::Filter(step) => {
+                    for (n, name) in step.names.iter().enumerate() {
+                        if !contains(&self.filters, name) {
+                            errors.push(ValidationError::new(
+                                format!("pipeline[{}].names[{}]", idx, n),
+                                ValidationErrorKind::MissingFilter(name.clone()),
+                            ));
+                        }
+                    }
+                }
+                PipelineStep::Mixer(step) => {
+                    if !contains(&self.mixers, &step.name) {
+                        errors.push(ValidationError::new(
+                            format!("pipeline[{}].name", idx),
+                            ValidationErrorKind::MissingMixer(step.name.clone()),
+                        ));
+                    }
+                }
+                PipelineStep::Processor(step) => {
+                    if !contains(&self.processors, &step.name) {
+                        errors.push(ValidationError::new(
+                            format!("pipeline[{}].name", idx),
+                            ValidationErrorKind::MissingProcessor(step.name.clone()),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn contains<T>(map: &Option<IndexMap<String, T>>, name: &str) -> bool {
+    map.as_ref().is_some_and(|map| map.contains_key(name))
+}
+
+fn validate_mixer(path: &str, mixer: &Mixer, errors: &mut Vec<ValidationError>) {
+    let mut seen = vec![false; mixer.channels.out];
+    for (m, mapping) in mixer.mapping.iter().enumerate() {
+        if mapping.dest >= mixer.channels.out {
+            errors.push(ValidationError::new(
+                format!("{}.mapping[{}].dest", path, m),
+                ValidationErrorKind::MixerDestOutOfRange {
+                    dest: mapping.dest,
+                    out: mixer.channels.out,
+                },
+            ));
+        } else if seen[mapping.dest] {
+            errors.push(ValidationError::new(
+                format!("{}.mapping[{}].dest", path, m),
+                ValidationErrorKind::DuplicateMixerDest(mapping.dest),
+            ));
+        } else {
+            seen[mapping.dest] = true;
+        }
+        for (s, source) in mapping.sources.iter().enumerate() {
+            if source.channel >= mixer.channels.r#in {
+                errors.push(ValidationError::new(
+                    format!("{}.mapping[{}].sources[{}].channel", path, m, s),
+                    ValidationErrorKind::MixerSourceOutOfRange {
+                        channel: source.channel,
+                        r#in: mixer.channels.r#in,
+                    },
+                ));
+            }
+        }
+    }
+    if let Some(labels) = &mixer.labels {
+        if labels.len() != mixer.channels.out {
+            errors.push(ValidationError::new(
+                format!("{}.labels", path),
+                ValidationErrorKind::MixerLabelCount {
+                    labels: labels.len(),
+                    out: mixer.channels.out,
+                },
+            ));
+        }
+    }
+}
+
+fn validate_channel_list(
+    path: &str,
+    list: &Option<Vec<usize>>,
+    channels: usize,
+    errors: &mut Vec<ValidationError>,
+) {
+    let Some(list) = list else {
+        return;
+    };
+    for (idx, channel) in list.iter().enumerate() {
+        if *channel >= channels {
+            errors.push(ValidationError::new(
+                format!("{}[{}]", path, idx),
+                ValidationErrorKind::ProcessorChannelOutOfRange {
+                    channel: *channel,
+                    channels,
+                },
+            ));
+        } else if list[..idx].contains(channel) {
+            errors.push(ValidationError::new(
+                format!("{}[{}]", path, idx),
+                ValidationErrorKind::DuplicateProcessorChannel(*channel),
+            ));
+        }
+    }
+}
+
+fn validate_processor(path: &str, processor: &Processor, errors: &mut Vec<ValidationError>) {
+    match processor {
+        Processor::Compressor { parameters, .. } => {
+            for (field, list) in [
+                ("monitor_channels", &parameters.monitor_channels),
+                ("process_channels", &parameters.process_channels),
+            ] {
+                validate_channel_list(
+                    &format!("{}.parameters.{}", path, field),
+                    list,
+                    parameters.channels,
+                    errors,
+                );
+            }
+        }
+        Processor::NoiseGate { parameters, .. } => {
+            for (field, list) in [
+                ("monitor_channels", &parameters.monitor_channels),
+                ("process_channels", &parameters.process_channels),
+            ] {
+                validate_channel_list(
+                    &format!("{}.parameters.{}", path, field),
+                    list,
+                    parameters.channels,
+                    errors,
+                );
+            }
+        }
+        Processor::RACE { parameters, .. } => {
+            for (field, channel) in [
+                ("channel_a", parameters.channel_a),
+                ("channel_b", parameters.channel_b),
+            ] {
+                if channel >= parameters.channels {
+                    errors.push(ValidationError::new(
+                        format!("{}.parameters.{}", path, field),
+                        ValidationErrorKind::ProcessorChannelOutOfRange {
+                            channel,
+                            channels: parameters.channels,
+                        },
+                    ));
+                }
+            }
+            if parameters.channel_a == parameters.channel_b {
+                errors.push(ValidationError::new(
+                    format!("{}.parameters.channel_b", path),
+                    ValidationErrorKind::DuplicateProcessorChannel(parameters.channel_b),
+                ));
+            }
+        }
+    }
+}
+
+// --- Parameter ranges ---
+
+// Upper limits, larger values are almost certainly a mistake.
+const MAX_Q: f64 = 100.0;
+// Octaves.
+const MAX_BANDWIDTH: f64 = 10.0;
+// dB per octave, the steepest a second order shelf can be.
+const MAX_SHELF_SLOPE: f64 = 12.0;
+
+struct RangeCheck<'a> {
+    path: &'a str,
+    nyquist: f64,
+    errors: &'a mut Vec<ValidationError>,
+}
+
+impl RangeCheck<'_> {
+    fn freq(&mut self, field: &str, freq: f64) {
+        let valid = freq > 0.0 && freq < self.nyquist;
+        if !valid {
+            self.errors.push(ValidationError::new(
+                format!("{}.{}", self.path, field),
+                ValidationErrorKind::FrequencyOutOfRange {
+                    freq,
+                    nyquist: self.nyquist,
+                },
+            ));
+        }
+    }
+
+    fn positive(&mut self, field: &str, value: f64, max: f64) {
+        let valid = value > 0.0 && value <= max;
+        if !valid {
+            self.errors.push(ValidationError::new(
+                format!("{}.{}", self.path, field),
+                ValidationErrorKind::ParameterOutOfRange {
+                    value,
+                    min: 0.0,
+                    max,
+                },
+            ));
+        }
+    }
+
+    fn width(&mut self, width: &NotchWidth) {
+        match width {
+            NotchWidth::Q { freq, q } => {
+                self.freq("freq", *freq);
+                self.positive("q", *q, MAX_Q);
+            }
+            NotchWidth::Bandwidth { freq, bandwidth } => {
+                self.freq("freq", *freq);
+                self.positive("bandwidth", *bandwidth, MAX_BANDWIDTH);
+            }
+        }
+    }
+
+    fn shelf(&mut self, steepness: &ShelfSteepness) {
+        match steepness {
+            ShelfSteepness::Q { freq, q, .. } => {
+                self.freq("freq", *freq);
+                self.positive("q", *q, MAX_Q);
+            }
+            ShelfSteepness::Slope { freq, slope, .. } => {
+                self.freq("freq", *freq);
+                self.positive("slope", *slope, MAX_SHELF_SLOPE);
+            }
+        }
+    }
+}
+
+fn validate_signal(devices: &Devices, errors: &mut Vec<ValidationError>) {
+    let CaptureDevice::SignalGenerator { signal, .. } = &devices.capture else {
+        return;
+    };
+    let (Signal::Sine { freq, .. } | Signal::Square { freq, .. }) = signal else {
+        return;
+    };
+    // The generator runs at the capture rate, before any resampling.
+    let samplerate = devices.capture_samplerate.unwrap_or(devices.samplerate);
+    RangeCheck {
+        path: "devices.capture.signal",
+        nyquist: samplerate as f64 / 2.0,
+        errors,
+    }
+    .freq("freq", *freq);
+}
+
+fn validate_filter(
+    path: &str,
+    filter: &Filter,
+    devices: &Devices,
+    errors: &mut Vec<ValidationError>,
+) {
+    let path = format!("{}.parameters", path);
+    let mut check = RangeCheck {
+        path: &path,
+        nyquist: devices.samplerate as f64 / 2.0,
+        errors,
+    };
+    match filter {
+        Filter::Biquad { parameters, .. } => match parameters {
+            BiquadParameters::Free { .. } => {}
+            BiquadParameters::Highpass { freq, q } | BiquadParameters::Lowpass { freq, q } => {
+                check.freq("freq", *freq);
+                check.positive("q", *q, MAX_Q);
+            }
+            BiquadParameters::Peaking(PeakingWidth::Q { freq, q, .. }) => {
+                check.freq("freq", *freq);
+                check.positive("q", *q, MAX_Q);
+            }
+            BiquadParameters::Peaking(PeakingWidth::Bandwidth {
+                freq, bandwidth, ..
+            }) => {
+                check.freq("freq", *freq);
+                check.positive("bandwidth", *bandwidth, MAX_BANDWIDTH);
+            }
+            BiquadParameters::Highshelf(steepness) | BiquadParameters::Lowshelf(steepness) => {
+                check.shelf(steepness)
+            }
+            BiquadParameters::HighshelfFO { freq, .. }
+            | BiquadParameters::LowshelfFO { freq, .. }
+            | BiquadParameters::HighpassFO { freq }
+            | BiquadParameters::LowpassFO { freq }
+            | BiquadParameters::AllpassFO { freq } => check.freq("freq", *freq),
+            BiquadParameters::Allpass(width)
+            | BiquadParameters::Bandpass(width)
+            | BiquadParameters::Notch(width) => check.width(width),
+            BiquadParameters::GeneralNotch(parameters) => {
+                check.freq("freq_p", parameters.freq_p);
+                check.freq("freq_z", parameters.freq_z);
+                check.positive("q_p", parameters.q_p, MAX_Q);
+            }
+            BiquadParameters::LinkwitzTransform {
+                freq_act,
+                q_act,
+                freq_target,
+                q_target,
+            } => {
+                check.freq("freq_act", *freq_act);
+                check.positive("q_act", *q_act, MAX_Q);
+                check.freq("freq_target", *freq_target);
+                check.positive("q_target", *q_target, MAX_Q);
+            }
+        },
+        Filter::BiquadCombo { parameters, .. } => match parameters {
+            BiquadComboParameters::LinkwitzRileyHighpass { freq, .. }
+            | BiquadComboParameters::LinkwitzRileyLowpass { freq, .. }
+            | BiquadComboParameters::ButterworthHighpass { freq, .. }
+            | BiquadComboParameters::ButterworthLowpass { freq, .. } => check.freq("freq", *freq),
+            BiquadComboParameters::Tilt { .. } => {}
+            BiquadComboParameters::FivePointPeq {
+                fls,
+                qls,
+                fp1,
+                qp1,
+                fp2,
+                qp2,
+                fp3,
+                qp3,
+                fhs,
+                qhs,
+                ..
+            } => {
+                for (freq_field, freq, q_field, q) in [
+                    ("fls", fls, "qls", qls),
+                    ("fp1", fp1, "qp1", qp1),
+                    ("fp2", fp2, "qp2", qp2),
+                    ("fp3", fp3, "qp3", qp3),
+                    ("fhs", fhs, "qhs", qhs),
+                ] {
+                    check.freq(freq_field, *freq);
+                    check.positive(q_field, *q, MAX_Q);
+                }
+            }
+            BiquadComboParameters::GraphicEqualizer(parameters) => {
+                for (field, freq) in [
+                    ("freq_min", parameters.freq_min),
+                    ("freq_max", parameters.freq_max),
+                ] {
+                    if let Some(freq) = freq {
+                        check.freq(field, freq as f64);
+                    }
+                }
+            }
+        },
+        Filter::Dither { parameters, .. } => {
+            let Some(max) = devices.playback.sample_bits() else {
+                return;
+            };
+            let bits = parameters.bits();
+            if !(2..=max).contains(&bits) {
+                check.errors.push(ValidationError::new(
+                    format!("{}.bits", path),
+                    ValidationErrorKind::DitherBitsOutOfRange { bits, max },
+                ));
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = r#"---
+devices:
+  samplerate: 48000
+  chunksize: 1024
+  capture:
+    type: Stdin
+    channels: 2
+    format: S16_LE
+  playback:
+    type: Stdout
+    channels: 2
+    format: S16_LE
+"#;
+
+    #[test]
+    fn test_validate_ok() {
+        let yaml = format!(
+            "{}{}",
+            BASE,
+            r#"
+filters:
+  vol:
+    type: Gain
+    parameters:
+      gain: -3
+mixers:
+  swap:
+    channels:
+      in: 2
+      out: 2
+    mapping:
+      - dest: 0
+        sources:
+          - channel: 1
+      - dest: 1
+        sources:
+          - channel: 0
+pipeline:
+  - type: Mixer
+    name: swap
+  - type: Filter
+    names: [vol]
+"#
+        );
+        let config = Configuration::from_yaml_string(&yaml).unwrap();
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_reports_paths() {
+        let yaml = format!(
+            "{}{}",
+            BASE,
+            r#"
+filters:
+  vol:
+    type: Gain
+    parameters:
+      gain: -3
+mixers:
+  broken:
+    channels:
+      in: 2
+      out: 2
+    mapping:
+      - dest: 2
+        sources:
+          - channel: 0
+      - dest: 0
+        sources:
+          - channel: 5
+processors:
+  race:
+    type: RACE
+    parameters:
+      channels: 2
+      channel_a: 0
+      channel_b: 2
+      delay: 1
+      attenuation: 3
+pipeline:
+  - type: Mixer
+    name: missing_mixer
+  - type: Processor
+    name: race
+  - type: Filter
+    channels: [0]
+    names: [vol, nope]
+"#
+        );
+        let config = Configuration::from_yaml_string(&yaml).unwrap();
+        let errors = config.validate().unwrap_err();
+        let paths: Vec<&str> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "mixers.broken.mapping[0].dest",
+                "mixers.broken.mapping[1].sources[0].channel",
+                "processors.race.parameters.channel_b",
+                "pipeline[0].name",
+                "pipeline[2].names[1]",
+            ]
+        );
+        assert_eq!(
+            errors[4].kind,
+            ValidationErrorKind::MissingFilter("nope".to_string())
+        );
+        assert_eq!(
+            errors[4].to_string(),
+            "pipeline[2].names[1]: filter 'nope' is not defined in 'filters'"
+        );
+    }
+
+    #[test]
+    fn test_validate_ranges() {
+        let yaml = format!(
+            "{}{}",
+            BASE.replace("samplerate: 48000", "samplerate: 44100"),
+            r#"
+filters:
+  air:
+    type: Biquad
+    parameters:
+      type: Peaking
+      freq: 23000
+      q: 0.7
+      gain: 2
+  bass:
+    type: Biquad
+    parameters:
+      type: Lowshelf
+      freq: 80
+      slope: 15
+      gain: 4
+  rumble:
+    type: Biquad
+    parameters:
+      type: Highpass
+      freq: 20
+      q: 0
+  geq:
+    type: BiquadCombo
+    parameters:
+      type: GraphicEqualizer
+      freq_max: 20000
+      gains: [0, 1, 2]
+  dither:
+    type: Dither
+    parameters:
+      type: Highpass
+      bits: 24
+"#
+        );
+        let config = Configuration::from_yaml_string(&yaml).unwrap();
+        let errors = config.validate().unwrap_err();
+        let paths: Vec<&str> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "filters.air.parameters.freq",
+                "filters.bass.parameters.slope",
+                "filters.rumble.parameters.q",
+                "filters.dither.parameters.bits",
+            ]
+        );
+        assert_eq!(
+            errors[0].to_string(),
+            "filters.air.parameters.freq: frequency 23000 Hz must be above 0 and below \
+             the Nyquist frequency of 22050 Hz"
+        );
+        assert_eq!(
+            errors[3].kind,
+            ValidationErrorKind::DitherBitsOutOfRange { bits: 24, max: 16 }
+        );
+    }
+}
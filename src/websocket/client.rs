@@ -0,0 +1,346 @@
+use std::fmt;
+use std::net::TcpStream;
+
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+use super::messages::*;
+use crate::types::Configuration;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClientError {
+    /// The connection failed or was closed.
+    Connection(String),
+    /// CamillaDSP sent something that is not a valid reply to the command.
+    Protocol(String),
+    /// CamillaDSP answered the command with an error result.
+    Failed(String),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Connection(reason) => write!(f, "connection error: {}", reason),
+            ClientError::Protocol(reason) => write!(f, "protocol error: {}", reason),
+            ClientError::Failed(command) => write!(f, "command {} failed", command),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<tungstenite::Error> for ClientError {
+    fn from(error: tungstenite::Error) -> Self {
+        ClientError::Connection(error.to_string())
+    }
+}
+
+/// State and signal levels, as read by `Client::status`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Status {
+    pub state: ProcessingState,
+    pub levels: SignalLevels,
+}
+
+/// Blocking client for the websocket server of a running CamillaDSP.
+pub struct Client {
+    socket: WebSocket<MaybeTlsStream<TcpStream>>,
+}
+
+fn command_name(command: &Command) -> String {
+    match serde_json::to_value(command) {
+        Ok(serde_json::Value::String(name)) => name,
+        Ok(serde_json::Value::Object(map)) => map.keys().next().cloned().unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+fn value<T>(command: &Command, response: Response<T>) -> Result<T, ClientError> {
+    if !response.is_ok() {
+        return Err(ClientError::Failed(command_name(command)));
+    }
+    response.value.ok_or_else(|| {
+        ClientError::Protocol(format!("{} reply has no value", command_name(command)))
+    })
+}
+
+fn done(command: &Command, response: Response<()>) -> Result<(), ClientError> {
+    if response.is_ok() {
+        Ok(())
+    } else {
+        Err(ClientError::Failed(command_name(command)))
+    }
+}
+
+fn unexpected(command: &Command, reply: Reply) -> ClientError {
+    ClientError::Protocol(format!(
+        "unexpected reply to {}: {:?}",
+        command_name(command),
+        reply
+    ))
+}
+
+impl Client {
+    /// Connect to a CamillaDSP websocket server, for example `ws://127.0.0.1:1234`.
+    pub fn connect(url: &str) -> Result<Self, ClientError> {
+        let (socket, _) = tungstenite::connect(url)?;
+        Ok(Client { socket })
+    }
+
+    /// Send a command and wait for its reply.
+    pub fn request(&mut self, command: &Command) -> Result<Reply, ClientError> {
+        let text =
+            serde_json::to_string(command).map_err(|e| ClientError::Protocol(e.to_string()))?;
+        self.socket.send(Message::text(text))?;
+        loop {
+            match self.socket.read()? {
+                Message::Text(text) => {
+                    return match serde_json::from_str(text.as_str()) {
+                        Ok(Reply::Invalid { error }) => Err(ClientError::Protocol(error)),
+                        Ok(reply) => Ok(reply),
+                        Err(e) => Err(ClientError::Protocol(e.to_string())),
+                    };
+                }
+                Message::Close(_) => {
+                    return Err(ClientError::Connection("closed by server".to_string()))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn close(mut self) -> Result<(), ClientError> {
+        self.socket.close(None)?;
+        // Wait for the server to confirm.
+        loop {
+            match self.socket.read() {
+                Ok(_) => {}
+                Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    pub fn version(&mut self) -> Result<String, ClientError> {
+        let command = Command::GetVersion;
+        match self.request(&command)? {
+            Reply::GetVersion(response) => value(&command, response),
+            other => Err(unexpected(&command, other)),
+        }
+    }
+
+    pub fn state(&mut self) -> Result<ProcessingState, ClientError> {
+        let command = Command::GetState;
+        match self.request(&command)? {
+            Reply::GetState(response) => value(&command, response),
+            other => Err(unexpected(&command, other)),
+        }
+    }
+
+    pub fn stop_reason(&mut self) -> Result<StopReason, ClientError> {
+        let command = Command::GetStopReason;
+        match self.request(&command)? {
+            Reply::GetStopReason(response) => value(&command, response),
+            other => Err(unexpected(&command, other)),
+        }
+    }
+
+    /// Levels since the last call to this method by this client.
+    pub fn signal_levels(&mut self) -> Result<SignalLevels, ClientError> {
+        let command = Command::GetSignalLevelsSinceLast;
+        match self.request(&command)? {
+            Reply::GetSignalLevelsSinceLast(response) => value(&command, response),
+            other => Err(unexpected(&command, other)),
+        }
+    }
+
+    /// Read the processing state and the signal levels, for polling.
+    pub fn status(&mut self) -> Result<Status, ClientError> {
+        Ok(Status {
+            state: self.state()?,
+            levels: self.signal_levels()?,
+        })
+    }
+
+    /// The active config.
+    pub fn config(&mut self) -> Result<Configuration, ClientError> {
+        let command = Command::GetConfigJson;
+        match self.request(&command)? {
+            Reply::GetConfigJson(response) => value(&command, response).map(|config| *config.0),
+            other => Err(unexpected(&command, other)),
+        }
+    }
+
+    /// Send a new config, it is applied right away.
+    pub fn set_config(&mut self, config: &Configuration) -> Result<(), ClientError> {
+        let command = Command::set_config_json(config.clone());
+        match self.request(&command)? {
+            Reply::SetConfigJson(response) => done(&command, response),
+            other => Err(unexpected(&command, other)),
+        }
+    }
+
+    pub fn reload(&mut self) -> Result<(), ClientError> {
+        let command = Command::Reload;
+        match self.request(&command)? {
+            Reply::Reload(response) => done(&command, response),
+            other => Err(unexpected(&command, other)),
+        }
+    }
+
+    pub fn fader_volume(&mut self, fader: Fader) -> Result<f32, ClientError> {
+        let command = Command::GetFaderVolume(fader);
+        match self.request(&command)? {
+            Reply::GetFaderVolume(response) => value(&command, response).map(|(_, volume)| volume),
+            other => Err(unexpected(&command, other)),
+        }
+    }
+
+    pub fn set_fader_volume(&mut self, fader: Fader, volume: f32) -> Result<(), ClientError> {
+        let command = Command::SetFaderVolume(fader, volume);
+        match self.request(&command)? {
+            Reply::SetFaderVolume(response) => done(&command, response),
+            other => Err(unexpected(&command, other)),
+        }
+    }
+
+    pub fn mute(&mut self) -> Result<bool, ClientError> {
+        let command = Command::GetMute;
+        match self.request(&command)? {
+            Reply::GetMute(response) => value(&command, response),
+            other => Err(unexpected(&command, other)),
+        }
+    }
+
+    pub fn set_mute(&mut self, mute: bool) -> Result<(), ClientError> {
+        let command = Command::SetMute(mute);
+        match self.request(&command)? {
+            Reply::SetMute(response) => done(&command, response),
+            other => Err(unexpected(&command, other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::VolumeFader;
+    use std::net::TcpListener;
+    use std::thread;
+
+    // A CamillaDSP stand-in that keeps a config and the fader volumes.
+    fn mock_server() -> (String, thread::JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+            let mut config: Option<Configuration> = None;
+            let mut volumes = [0.0_f32; 5];
+            loop {
+                let text = match socket.read() {
+                    Ok(Message::Text(text)) => text,
+                    // The close reply is sent by the next read, which then fails.
+                    Err(_) => break,
+                    Ok(_) => continue,
+                };
+                let reply = match serde_json::from_str::<Command>(text.as_str()) {
+                    Ok(Command::GetState) => {
+                        Reply::GetState(Response::ok(ProcessingState::Running))
+                    }
+                    Ok(Command::GetSignalLevelsSinceLast) => {
+                        Reply::GetSignalLevelsSinceLast(Response::ok(SignalLevels {
+                            playback_rms: vec![-20.0, -21.0],
+                            playback_peak: vec![-10.0, -11.0],
+                            capture_rms: vec![-20.0, -21.0],
+                            capture_peak: vec![-10.0, -11.0],
+                        }))
+                    }
+                    Ok(Command::SetConfigJson(JsonConfig(new))) => {
+                        config = Some(*new);
+                        Reply::SetConfigJson(Response::done())
+                    }
+                    Ok(Command::GetConfigJson) => match &config {
+                        Some(config) => {
+                            Reply::GetConfigJson(Response::ok(JsonConfig(Box::new(config.clone()))))
+                        }
+                        None => Reply::GetConfigJson(Response {
+                            result: WsResult::Error,
+                            value: None,
+                        }),
+                    },
+                    Ok(Command::SetFaderVolume(fader, volume)) => {
+                        volumes[fader.index()] = volume;
+                        Reply::SetFaderVolume(Response::done())
+                    }
+                    Ok(Command::GetFaderVolume(fader)) => {
+                        Reply::GetFaderVolume(Response::ok((fader, volumes[fader.index()])))
+                    }
+                    Ok(Command::Reload) => Reply::Reload(Response::error()),
+                    Ok(Command::GetMute) => Reply::GetVolume(Response::ok(0.0)),
+                    Ok(_) | Err(_) => Reply::Invalid {
+                        error: format!("unsupported command {}", text.as_str()),
+                    },
+                };
+                let text = serde_json::to_string(&reply).unwrap();
+                socket.send(Message::text(text)).unwrap();
+            }
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_client_against_mock_server() {
+        let (url, server) = mock_server();
+        let mut client = Client::connect(&url).unwrap();
+
+        assert_eq!(
+            client.config(),
+            Err(ClientError::Failed("GetConfigJson".to_string()))
+        );
+        let config = Configuration::from_yaml_string(
+            r#"---
+devices:
+  samplerate: 48000
+  chunksize: 1024
+  capture:
+    type: Stdin
+    channels: 2
+    format: S16_LE
+  playback:
+    type: Stdout
+    channels: 2
+    format: S16_LE
+filters:
+  vol:
+    type: Volume
+    parameters:
+      fader: Aux1
+"#,
+        )
+        .unwrap();
+        client.set_config(&config).unwrap();
+        assert_eq!(client.config().unwrap(), config);
+
+        let status = client.status().unwrap();
+        assert_eq!(status.state, ProcessingState::Running);
+        assert_eq!(status.levels.capture_peak, vec![-10.0, -11.0]);
+
+        let aux = Fader::from(VolumeFader::Aux1);
+        client.set_fader_volume(aux, -12.5).unwrap();
+        assert_eq!(client.fader_volume(aux).unwrap(), -12.5);
+        assert_eq!(client.fader_volume(Fader::Main).unwrap(), 0.0);
+
+        assert_eq!(
+            client.reload(),
+            Err(ClientError::Failed("Reload".to_string()))
+        );
+        assert!(matches!(client.mute(), Err(ClientError::Protocol(_))));
+        assert!(
+            matches!(client.version(), Err(ClientError::Protocol(e)) if e.contains("GetVersion"))
+        );
+
+        client.close().unwrap();
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,564 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::types::{Configuration, VolumeFader};
+
+// --- Fader index ---
+
+/// A volume fader as numbered by the websocket API: 0 is the main fader,
+/// 1 to 4 are the aux faders used by `Volume` filters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fader {
+    Main,
+    Aux(VolumeFader),
+}
+
+impl Fader {
+    pub fn index(self) -> usize {
+        match self {
+            Fader::Main => 0,
+            Fader::Aux(fader) => fader as usize,
+        }
+    }
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        let fader = match index {
+            0 => Fader::Main,
+            1 => Fader::Aux(VolumeFader::Aux1),
+            2 => Fader::Aux(VolumeFader::Aux2),
+            3 => Fader::Aux(VolumeFader::Aux3),
+            4 => Fader::Aux(VolumeFader::Aux4),
+            _ => return None,
+        };
+        Some(fader)
+    }
+}
+
+impl From<VolumeFader> for Fader {
+    fn from(fader: VolumeFader) -> Self {
+        Fader::Aux(fader)
+    }
+}
+
+impl Serialize for Fader {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.index() as u64)
+    }
+}
+
+impl<'de> Deserialize<'de> for Fader {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let index = usize::deserialize(deserializer)?;
+        Fader::from_index(index)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid fader index {}", index)))
+    }
+}
+
+// --- Volume adjustment ---
+
+/// The argument of `AdjustVolume` and `AdjustFaderVolume`: a change in dB,
+/// optionally with the lower and upper limits the resulting volume is clamped to.
+/// Sent as `5.0` or as `[5.0, -30.0, 10.0]`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum VolumeAdjust {
+    Step(f32),
+    /// Change, minimum and maximum volume, in that order.
+    Limited(f32, f32, f32),
+}
+
+impl From<f32> for VolumeAdjust {
+    fn from(value: f32) -> Self {
+        VolumeAdjust::Step(value)
+    }
+}
+
+// --- Config payloads ---
+
+/// A config sent as a JSON string, as used by `SetConfigJson` and `GetConfigJson`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonConfig(pub Box<Configuration>);
+
+/// A config sent as a YAML string, as used by `SetConfig` and `GetConfig`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct YamlConfig(pub Box<Configuration>);
+
+impl Serialize for JsonConfig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let text = serde_json::to_string(&self.0).map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&text)
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        serde_json::from_str(&text)
+            .map(JsonConfig)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for YamlConfig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let text = self.0.to_yaml_string().map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&text)
+    }
+}
+
+impl<'de> Deserialize<'de> for YamlConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Configuration::from_yaml_string(&text)
+            .map(|config| YamlConfig(Box::new(config)))
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+// --- Commands ---
+
+/// A request to CamillaDSP. Commands without arguments are sent as a plain
+/// string, for example `"GetState"`, the others as `{"SetVolume": -10.0}`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Command {
+    GetVersion,
+    GetState,
+    GetStopReason,
+    GetCaptureRate,
+    GetSignalRange,
+    GetRateAdjust,
+    GetBufferLevel,
+    GetClippedSamples,
+    ResetClippedSamples,
+    GetProcessingLoad,
+    GetUpdateInterval,
+    SetUpdateInterval(usize),
+
+    GetCaptureSignalRms,
+    GetCaptureSignalRmsSince(f32),
+    GetCaptureSignalRmsSinceLast,
+    GetCaptureSignalPeak,
+    GetCaptureSignalPeakSince(f32),
+    GetCaptureSignalPeakSinceLast,
+    GetPlaybackSignalRms,
+    GetPlaybackSignalRmsSince(f32),
+    GetPlaybackSignalRmsSinceLast,
+    GetPlaybackSignalPeak,
+    GetPlaybackSignalPeakSince(f32),
+    GetPlaybackSignalPeakSinceLast,
+    GetSignalLevels,
+    GetSignalLevelsSince(f32),
+    GetSignalLevelsSinceLast,
+    GetSignalPeaksSinceStart,
+    ResetSignalPeaksSinceStart,
+
+    GetVolume,
+    SetVolume(f32),
+    AdjustVolume(VolumeAdjust),
+    GetMute,
+    SetMute(bool),
+    ToggleMute,
+    GetFaders,
+    GetFaderVolume(Fader),
+    SetFaderVolume(Fader, f32),
+    SetFaderExternalVolume(Fader, f32),
+    AdjustFaderVolume(Fader, VolumeAdjust),
+    GetFaderMute(Fader),
+    SetFaderMute(Fader, bool),
+    ToggleFaderMute(Fader),
+
+    GetConfig,
+    GetConfigJson,
+    GetConfigTitle,
+    GetConfigDescription,
+    GetPreviousConfig,
+    GetConfigFilePath,
+    SetConfig(YamlConfig),
+    SetConfigJson(JsonConfig),
+    SetConfigFilePath(String),
+    ReadConfig(String),
+    ReadConfigFile(String),
+    ValidateConfig(String),
+    Reload,
+    GetStateFilePath,
+    GetStateFileUpdated,
+
+    GetSupportedDeviceTypes,
+    GetAvailableCaptureDevices(String),
+    GetAvailablePlaybackDevices(String),
+
+    Stop,
+    Exit,
+}
+
+impl Command {
+    pub fn set_config(config: Configuration) -> Self {
+        Command::SetConfig(YamlConfig(Box::new(config)))
+    }
+
+    pub fn set_config_json(config: Configuration) -> Self {
+        Command::SetConfigJson(JsonConfig(Box::new(config)))
+    }
+}
+
+// --- Replies ---
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WsResult {
+    Ok,
+    Error,
+}
+
+/// The body of every reply, `{"result": "Ok", "value": ...}`.
+/// Replies to commands that only perform an action have no value.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Response<T> {
+    pub result: WsResult,
+    #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+    pub value: Option<T>,
+}
+
+impl<T> Response<T> {
+    pub fn ok(value: T) -> Self {
+        Response {
+            result: WsResult::Ok,
+            value: Some(value),
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.result == WsResult::Ok
+    }
+}
+
+impl Response<()> {
+    pub fn done() -> Self {
+        Response {
+            result: WsResult::Ok,
+            value: None,
+        }
+    }
+
+    pub fn error() -> Self {
+        Response {
+            result: WsResult::Error,
+            value: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProcessingState {
+    #[serde(rename = "RUNNING")]
+    Running,
+    #[serde(rename = "PAUSED")]
+    Paused,
+    #[serde(rename = "INACTIVE")]
+    Inactive,
+    #[serde(rename = "STARTING")]
+    Starting,
+    #[serde(rename = "STALLED")]
+    Stalled,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StopReason {
+    None,
+    Done,
+    CaptureError(String),
+    PlaybackError(String),
+    UnknownError(String),
+    CaptureFormatChanged(usize),
+    PlaybackFormatChanged(usize),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SignalLevels {
+    pub playback_rms: Vec<f32>,
+    pub playback_peak: Vec<f32>,
+    pub capture_rms: Vec<f32>,
+    pub capture_peak: Vec<f32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SignalPeaks {
+    pub playback: Vec<f32>,
+    pub capture: Vec<f32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FaderLevel {
+    pub volume: f32,
+    pub mute: bool,
+}
+
+/// A reply from CamillaDSP, named after the command it answers:
+/// `{"GetVolume": {"result": "Ok", "value": -10.0}}`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Reply {
+    GetVersion(Response<String>),
+    GetState(Response<ProcessingState>),
+    GetStopReason(Response<StopReason>),
+    GetCaptureRate(Response<usize>),
+    GetSignalRange(Response<f32>),
+    GetRateAdjust(Response<f32>),
+    GetBufferLevel(Response<usize>),
+    GetClippedSamples(Response<usize>),
+    ResetClippedSamples(Response<()>),
+    GetProcessingLoad(Response<f32>),
+    GetUpdateInterval(Response<usize>),
+    SetUpdateInterval(Response<()>),
+
+    GetCaptureSignalRms(Response<Vec<f32>>),
+    GetCaptureSignalRmsSince(Response<Vec<f32>>),
+    GetCaptureSignalRmsSinceLast(Response<Vec<f32>>),
+    GetCaptureSignalPeak(Response<Vec<f32>>),
+    GetCaptureSignalPeakSince(Response<Vec<f32>>),
+    GetCaptureSignalPeakSinceLast(Response<Vec<f32>>),
+    GetPlaybackSignalRms(Response<Vec<f32>>),
+    GetPlaybackSignalRmsSince(Response<Vec<f32>>),
+    GetPlaybackSignalRmsSinceLast(Response<Vec<f32>>),
+    GetPlaybackSignalPeak(Response<Vec<f32>>),
+    GetPlaybackSignalPeakSince(Response<Vec<f32>>),
+    GetPlaybackSignalPeakSinceLast(Response<Vec<f32>>),
+    GetSignalLevels(Response<SignalLevels>),
+    GetSignalLevelsSince(Response<SignalLevels>),
+    GetSignalLevelsSinceLast(Response<SignalLevels>),
+    GetSignalPeaksSinceStart(Response<SignalPeaks>),
+    ResetSignalPeaksSinceStart(Response<()>),
+
+    GetVolume(Response<f32>),
+    SetVolume(Response<()>),
+    AdjustVolume(Response<f32>),
+    GetMute(Response<bool>),
+    SetMute(Response<()>),
+    ToggleMute(Response<bool>),
+    GetFaders(Response<Vec<FaderLevel>>),
+    GetFaderVolume(Response<(Fader, f32)>),
+    SetFaderVolume(Response<()>),
+    SetFaderExternalVolume(Response<()>),
+    AdjustFaderVolume(Response<(Fader, f32)>),
+    GetFaderMute(Response<(Fader, bool)>),
+    SetFaderMute(Response<()>),
+    ToggleFaderMute(Response<(Fader, bool)>),
+
+    GetConfig(Response<YamlConfig>),
+    GetConfigJson(Response<JsonConfig>),
+    GetConfigTitle(Response<String>),
+    GetConfigDescription(Response<String>),
+    GetPreviousConfig(Response<YamlConfig>),
+    GetConfigFilePath(Response<String>),
+    SetConfig(Response<()>),
+    SetConfigJson(Response<()>),
+    SetConfigFilePath(Response<()>),
+    /// The config as YAML when it is valid, otherwise the error message.
+    ReadConfig(Response<String>),
+    ReadConfigFile(Response<String>),
+    ValidateConfig(Response<String>),
+    Reload(Response<()>),
+    GetStateFilePath(Response<String>),
+    GetStateFileUpdated(Response<bool>),
+
+    GetSupportedDeviceTypes(Response<(Vec<String>, Vec<String>)>),
+    GetAvailableCaptureDevices(Response<Vec<(String, String)>>),
+    GetAvailablePlaybackDevices(Response<Vec<(String, String)>>),
+
+    Stop(Response<()>),
+    Exit(Response<()>),
+
+    /// Sent when the command could not be parsed.
+    Invalid {
+        error: String,
+    },
+}
+
+impl Reply {
+    /// The result reported by CamillaDSP, `None` for an `Invalid` reply.
+    pub fn result(&self) -> Option<WsResult> {
+        match self {
+            Reply::GetVersion(Response { result, .. })
+            | Reply::GetState(Response { result, .. })
+            | Reply::GetStopReason(Response { result, .. })
+            | Reply::GetCaptureRate(Response { result, .. })
+            | Reply::GetSignalRange(Response { result, .. })
+            | Reply::GetRateAdjust(Response { result, .. })
+            | Reply::GetBufferLevel(Response { result, .. })
+            | Reply::GetClippedSamples(Response { result, .. })
+            | Reply::ResetClippedSamples(Response { result, .. })
+            | Reply::GetProcessingLoad(Response { result, .. })
+            | Reply::GetUpdateInterval(Response { result, .. })
+            | Reply::SetUpdateInterval(Response { result, .. })
+            | Reply::GetCaptureSignalRms(Response { result, .. })
+            | Reply::GetCaptureSignalRmsSince(Response { result, .. })
+            | Reply::GetCaptureSignalRmsSinceLast(Response { result, .. })
+            | Reply::GetCaptureSignalPeak(Response { result, .. })
+            | Reply::GetCaptureSignalPeakSince(Response { result, .. })
+            | Reply::GetCaptureSignalPeakSinceLast(Response { result, .. })
+            | Reply::GetPlaybackSignalRms(Response { result, .. })
+            | Reply::GetPlaybackSignalRmsSince(Response { result, .. })
+            | Reply::GetPlaybackSignalRmsSinceLast(Response { result, .. })
+            | Reply::GetPlaybackSignalPeak(Response { result, .. })
+            | Reply::GetPlaybackSignalPeakSince(Response { result, .. })
+            | Reply::GetPlaybackSignalPeakSinceLast(Response { result, .. })
+            | Reply::GetSignalLevels(Response { result, .. })
+            | Reply::GetSignalLevelsSince(Response { result, .. })
+            | Reply::GetSignalLevelsSinceLast(Response { result, .. })
+            | Reply::GetSignalPeaksSinceStart(Response { result, .. })
+            | Reply::ResetSignalPeaksSinceStart(Response { result, .. })
+            | Reply::GetVolume(Response { result, .. })
+            | Reply::SetVolume(Response { result, .. })
+            | Reply::AdjustVolume(Response { result, .. })
+            | Reply::GetMute(Response { result, .. })
+            | Reply::SetMute(Response { result, .. })
+            | Reply::ToggleMute(Response { result, .. })
+            | Reply::GetFaders(Response { result, .. })
+            | Reply::GetFaderVolume(Response { result, .. })
+            | Reply::SetFaderVolume(Response { result, .. })
+            | Reply::SetFaderExternalVolume(Response { result, .. })
+            | Reply::AdjustFaderVolume(Response { result, .. })
+            | Reply::GetFaderMute(Response { result, .. })
+            | Reply::SetFaderMute(Response { result, .. })
+            | Reply::ToggleFaderMute(Response { result, .. })
+            | Reply::GetConfig(Response { result, .. })
+            | Reply::GetConfigJson(Response { result, .. })
+            | Reply::GetConfigTitle(Response { result, .. })
+            | Reply::GetConfigDescription(Response { result, .. })
+            | Reply::GetPreviousConfig(Response { result, .. })
+            | Reply::GetConfigFilePath(Response { result, .. })
+            | Reply::SetConfig(Response { result, .. })
+            | Reply::SetConfigJson(Response { result, .. })
+            | Reply::SetConfigFilePath(Response { result, .. })
+            | Reply::ReadConfig(Response { result, .. })
+            | Reply::ReadConfigFile(Response { result, .. })
+            | Reply::ValidateConfig(Response { result, .. })
+            | Reply::Reload(Response { result, .. })
+            | Reply::GetStateFilePath(Response { result, .. })
+            | Reply::GetStateFileUpdated(Response { result, .. })
+            | Reply::GetSupportedDeviceTypes(Response { result, .. })
+            | Reply::GetAvailableCaptureDevices(Response { result, .. })
+            | Reply::GetAvailablePlaybackDevices(Response { result, .. })
+            | Reply::Stop(Response { result, .. })
+            | Reply::Exit(Response { result, .. }) => Some(*result),
+            Reply::Invalid { .. } => None,
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.result() == Some(WsResult::Ok)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_json() {
+        let cases = [
+            (Command::GetState, r#""GetState""#),
+            (Command::SetVolume(-10.5), r#"{"SetVolume":-10.5}"#),
+            (
+                Command::SetFaderVolume(VolumeFader::Aux2.into(), -3.0),
+                r#"{"SetFaderVolume":[2,-3.0]}"#,
+            ),
+            (Command::GetFaderMute(Fader::Main), r#"{"GetFaderMute":0}"#),
+            (
+                Command::SetConfigFilePath("/etc/camilladsp.yml".to_string()),
+                r#"{"SetConfigFilePath":"/etc/camilladsp.yml"}"#,
+            ),
+            (
+                Command::AdjustVolume(VolumeAdjust::Step(-2.0)),
+                r#"{"AdjustVolume":-2.0}"#,
+            ),
+            (
+                Command::AdjustVolume(VolumeAdjust::Limited(5.0, -30.0, 10.0)),
+                r#"{"AdjustVolume":[5.0,-30.0,10.0]}"#,
+            ),
+            (
+                Command::AdjustFaderVolume(Fader::Main, VolumeAdjust::Limited(-1.5, -40.0, 0.0)),
+                r#"{"AdjustFaderVolume":[0,[-1.5,-40.0,0.0]]}"#,
+            ),
+        ];
+        for (command, json) in cases {
+            assert_eq!(serde_json::to_string(&command).unwrap(), json);
+            assert_eq!(serde_json::from_str::<Command>(json).unwrap(), command);
+        }
+        assert!(serde_json::from_str::<Command>(r#"{"GetFaderVolume":5}"#).is_err());
+    }
+
+    #[test]
+    fn test_reply_json() {
+        let reply: Reply =
+            serde_json::from_str(r#"{"GetVolume":{"result":"Ok","value":-12.0}}"#).unwrap();
+        assert_eq!(reply, Reply::GetVolume(Response::ok(-12.0)));
+        assert!(reply.is_ok());
+
+        let reply: Reply = serde_json::from_str(r#"{"SetVolume":{"result":"Error"}}"#).unwrap();
+        assert_eq!(reply, Reply::SetVolume(Response::error()));
+        assert!(!reply.is_ok());
+
+        let reply: Reply =
+            serde_json::from_str(r#"{"GetState":{"result":"Ok","value":"RUNNING"}}"#).unwrap();
+        assert_eq!(
+            reply,
+            Reply::GetState(Response::ok(ProcessingState::Running))
+        );
+
+        let reply: Reply = serde_json::from_str(
+            r#"{"GetStopReason":{"result":"Ok","value":{"CaptureFormatChanged":44100}}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            reply,
+            Reply::GetStopReason(Response::ok(StopReason::CaptureFormatChanged(44100)))
+        );
+
+        let reply: Reply =
+            serde_json::from_str(r#"{"GetFaderVolume":{"result":"Ok","value":[3,-20.0]}}"#)
+                .unwrap();
+        assert_eq!(
+            reply,
+            Reply::GetFaderVolume(Response::ok((VolumeFader::Aux3.into(), -20.0)))
+        );
+    }
+
+    #[test]
+    fn test_config_payloads() {
+        let config = Configuration::from_yaml_string(
+            r#"---
+devices:
+  samplerate: 48000
+  chunksize: 1024
+  capture:
+    type: Stdin
+    channels: 2
+    format: S16_LE
+  playback:
+    type: Stdout
+    channels: 2
+    format: S16_LE
+"#,
+        )
+        .unwrap();
+        let command = Command::set_config_json(config.clone());
+        let json = serde_json::to_value(&command).unwrap();
+        let payload = json["SetConfigJson"].as_str().unwrap();
+        assert!(payload.starts_with(r#"{"title":null"#));
+        assert_eq!(
+            serde_json::from_value::<Command>(json).unwrap(),
+            Command::set_config_json(config.clone())
+        );
+
+        let reply = serde_json::json!({
+            "GetConfigJson": {"result": "Ok", "value": serde_json::to_string(&config).unwrap()}
+        });
+        let reply: Reply = serde_json::from_value(reply).unwrap();
+        let Reply::GetConfigJson(Response {
+            value: Some(JsonConfig(received)),
+            ..
+        }) = reply
+        else {
+            panic!("expected a config");
+        };
+        assert_eq!(*received, config);
+    }
+}
@@ -0,0 +1,7 @@
+#[cfg(feature = "websocket-client")]
+mod client;
+mod messages;
+
+#[cfg(feature = "websocket-client")]
+pub use client::*;
+pub use messages::*;